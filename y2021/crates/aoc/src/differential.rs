@@ -0,0 +1,255 @@
+//! Differential testing: solvers that should agree run on the same inputs, and an input they
+//! disagree on is shrunk to a small counterexample.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Smaller versions of a value, most promising first. Shrinking stops at values without any.
+pub trait Shrink: Sized {
+    fn shrink(&self) -> Vec<Self> {
+        Vec::new()
+    }
+}
+
+macro_rules! shrink_unsigned {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<Self> {
+                let mut smaller = vec![0, self / 2, self.saturating_sub(1)];
+                smaller.dedup();
+                smaller.retain(|n| n < self);
+                smaller
+            }
+        }
+    )*};
+}
+
+macro_rules! shrink_signed {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<Self> {
+                let mut smaller = vec![0, self / 2, self - self.signum()];
+                // The same size but positive counts as smaller
+                if let Some(positive) = self.checked_neg().filter(|n| *n > 0) {
+                    smaller.push(positive);
+                }
+                smaller.dedup();
+                smaller.retain(|n| n != self && n.unsigned_abs() <= self.unsigned_abs());
+                smaller
+            }
+        }
+    )*};
+}
+
+shrink_unsigned!(u8, u16, u32, u64, usize);
+shrink_signed!(i8, i16, i32, i64, isize);
+
+impl Shrink for char {}
+
+impl Shrink for String {
+    fn shrink(&self) -> Vec<Self> {
+        let chars: Vec<char> = self.chars().collect();
+        chars
+            .shrink()
+            .into_iter()
+            .map(|chars| chars.into_iter().collect())
+            .collect()
+    }
+}
+
+/// Drops the first or second half, then single elements, then shrinks the elements in place.
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller: Vec<Vec<T>> = Vec::new();
+        if self.len() > 1 {
+            let half = self.len() / 2;
+            smaller.push(self[half..].to_vec());
+            smaller.push(self[..half].to_vec());
+        }
+        for index in 0..self.len() {
+            let mut without = self.clone();
+            without.remove(index);
+            smaller.push(without);
+        }
+        for (index, item) in self.iter().enumerate() {
+            for item in item.shrink() {
+                let mut shrunk = self.clone();
+                shrunk[index] = item;
+                smaller.push(shrunk);
+            }
+        }
+        smaller
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+        let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+/// Rows of equal length, shrinking by dropping whole rows and columns so it stays rectangular.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rectangle<T>(pub Vec<Vec<T>>);
+
+impl<T: Shrink + Clone> Shrink for Rectangle<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let rows = self.0.len();
+        let cols = self.0.first().map_or(0, |row| row.len());
+        let mut smaller = Vec::new();
+        for row in (0..rows).filter(|_| rows > 1) {
+            let mut cells = self.0.clone();
+            cells.remove(row);
+            smaller.push(Rectangle(cells));
+        }
+        for col in (0..cols).filter(|_| cols > 1) {
+            let mut cells = self.0.clone();
+            for row in cells.iter_mut() {
+                row.remove(col);
+            }
+            smaller.push(Rectangle(cells));
+        }
+        for (x, row) in self.0.iter().enumerate() {
+            for (y, cell) in row.iter().enumerate() {
+                for cell in cell.shrink() {
+                    let mut cells = self.0.clone();
+                    cells[x][y] = cell;
+                    smaller.push(Rectangle(cells));
+                }
+            }
+        }
+        smaller
+    }
+}
+
+/// `count` inputs made from a random number generator seeded with `seed`.
+pub fn inputs<I>(count: usize, seed: u64, mut make: impl FnMut(&mut StdRng) -> I) -> Vec<I> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count).map(|_| make(&mut rng)).collect()
+}
+
+/// The smallest input found on which `solver` disagrees with the reference.
+#[derive(Debug, Clone)]
+pub struct Counterexample<I, O> {
+    pub reference: &'static str,
+    pub solver: &'static str,
+    pub input: I,
+    pub expected: O,
+    pub found: O,
+    /// How many times the input got smaller.
+    pub shrinks: usize,
+}
+
+impl<I: Debug, O: Debug> Display for Counterexample<I, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} disagrees with {} after {} shrinks\ninput: {:?}\n{}: {:?}\n{}: {:?}",
+            self.solver,
+            self.reference,
+            self.shrinks,
+            self.input,
+            self.reference,
+            self.expected,
+            self.solver,
+            self.found
+        )
+    }
+}
+
+/// Shrinking gives up after this many steps and reports what it has.
+const MAX_SHRINKS: usize = 10_000;
+
+type Solver<'a, I, O> = Box<dyn Fn(&I) -> O + 'a>;
+
+/// A reference solver and the alternatives registered against it.
+pub struct Differential<'a, I, O> {
+    reference: (&'static str, Solver<'a, I, O>),
+    alternatives: Vec<(&'static str, Solver<'a, I, O>)>,
+    valid: Box<dyn Fn(&I) -> bool + 'a>,
+}
+
+impl<'a, I: Shrink + Clone + Debug, O: PartialEq + Debug> Differential<'a, I, O> {
+    pub fn new(name: &'static str, reference: impl Fn(&I) -> O + 'a) -> Self {
+        Differential {
+            reference: (name, Box::new(reference)),
+            alternatives: Vec::new(),
+            valid: Box::new(|_| true),
+        }
+    }
+
+    pub fn register(mut self, name: &'static str, solver: impl Fn(&I) -> O + 'a) -> Self {
+        self.alternatives.push((name, Box::new(solver)));
+        self
+    }
+
+    /// Only inputs passing `valid` are tried, shrunk ones included.
+    pub fn only(mut self, valid: impl Fn(&I) -> bool + 'a) -> Self {
+        self.valid = Box::new(valid);
+        self
+    }
+
+    fn disagreement(&self, solver: usize, input: &I) -> Option<(O, O)> {
+        if !(self.valid)(input) {
+            return None;
+        }
+        let expected = (self.reference.1)(input);
+        let found = (self.alternatives[solver].1)(input);
+        (expected != found).then_some((expected, found))
+    }
+
+    fn minimise(&self, solver: usize, mut input: I, mut outputs: (O, O)) -> Counterexample<I, O> {
+        let mut shrinks = 0;
+        'shrinking: while shrinks < MAX_SHRINKS {
+            for candidate in input.shrink() {
+                if let Some(found) = self.disagreement(solver, &candidate) {
+                    input = candidate;
+                    outputs = found;
+                    shrinks += 1;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+        Counterexample {
+            reference: self.reference.0,
+            solver: self.alternatives[solver].0,
+            input,
+            expected: outputs.0,
+            found: outputs.1,
+            shrinks,
+        }
+    }
+
+    /// Runs every solver on every input, the first disagreement is shrunk and returned.
+    pub fn check<C>(&self, inputs: C) -> Result<usize, Counterexample<I, O>>
+    where
+        C: IntoIterator<Item = I>,
+    {
+        let mut checked = 0;
+        for input in inputs {
+            for solver in 0..self.alternatives.len() {
+                if let Some(outputs) = self.disagreement(solver, &input) {
+                    return Err(self.minimise(solver, input.clone(), outputs));
+                }
+            }
+            if (self.valid)(&input) {
+                checked += 1;
+            }
+        }
+        Ok(checked)
+    }
+
+    /// Like `check`, panicking with the counterexample for use in tests.
+    pub fn assert<C>(&self, inputs: C)
+    where
+        C: IntoIterator<Item = I>,
+    {
+        if let Err(counterexample) = self.check(inputs) {
+            panic!("{}", counterexample);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+pub mod differential;
+pub mod y2021;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt::{Display, Formatter};
+
+/// How much input to generate. Every day picks its own defaults, sized like a personal input,
+/// for the values left out.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Params {
+    /// The same seed gives the same input.
+    pub seed: u64,
+    /// The number of lines, boards, segments, fish, crabs or displays.
+    pub count: Option<usize>,
+    /// The width of a line, number or grid.
+    pub width: Option<usize>,
+    /// The height of a grid.
+    pub height: Option<usize>,
+    /// The largest value in the input.
+    pub max: Option<u64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GenError {
+    UnknownYear {
+        year: u32,
+    },
+    UnknownDay {
+        day: u32,
+    },
+    /// The parameters ask for an input the puzzle can't have.
+    Impossible {
+        reason: String,
+    },
+}
+
+impl Display for GenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenError::UnknownYear { year } => write!(f, "No generators for {}", year),
+            GenError::UnknownDay { day } => write!(f, "No generator for day {}", day),
+            GenError::Impossible { reason } => write!(f, "Impossible input: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GenError {}
+
+/// A random puzzle input for `day` of `year`, every line ending in a newline.
+pub fn generate(year: u32, day: u32, params: &Params) -> Result<String, GenError> {
+    if year != 2021 {
+        return Err(GenError::UnknownYear { year });
+    }
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let rng = &mut rng;
+    let count = |default: usize| params.count.unwrap_or(default);
+    let width = |default: usize| params.width.unwrap_or(default);
+    let height = |default: usize| params.height.unwrap_or(default);
+    let max = |default: u64| params.max.unwrap_or(default);
+    match day {
+        1 => Ok(y2021::depths(rng, count(2000))),
+        2 => Ok(y2021::commands(rng, count(1000), max(9))),
+        3 => y2021::diagnostics(rng, count(1000), width(12)),
+        4 => y2021::bingo(rng, count(100), width(5), max(100)),
+        5 => Ok(y2021::vents(rng, count(500), max(990))),
+        6 => y2021::fish(rng, count(300), max(5)),
+        7 => Ok(y2021::crabs(rng, count(1000), max(1999))),
+        8 => Ok(y2021::displays(rng, count(200))),
+        9 => y2021::heightmap(rng, height(100), width(100)),
+        10 => Ok(y2021::navigation(rng, count(100), width(100))),
+        11 => y2021::octopuses(rng, height(10), width(10)),
+        _ => Err(GenError::UnknownDay { day }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differential::{inputs, Differential, Rectangle, Shrink};
+    use crate::y2021::{navigation_line, Chunks};
+    use crate::{generate, GenError, Params};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+
+    #[test]
+    fn reproducible() {
+        for day in 1..=11 {
+            let params = Params {
+                seed: 7,
+                ..Params::default()
+            };
+            let first = generate(2021, day, &params).unwrap();
+            assert_eq!(first, generate(2021, day, &params).unwrap());
+            assert!(first.ends_with('\n'));
+            let other = Params {
+                seed: 8,
+                ..Params::default()
+            };
+            assert_ne!(first, generate(2021, day, &other).unwrap());
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        let params = Params {
+            count: Some(20),
+            width: Some(7),
+            height: Some(4),
+            max: Some(60),
+            ..Params::default()
+        };
+        let lines = |day| generate(2021, day, &params).unwrap().lines().count();
+        for day in [1, 2, 3, 5, 8, 10] {
+            assert_eq!(lines(day), 20);
+        }
+        // Draws, then a blank line and seven rows for every board
+        assert_eq!(lines(4), 1 + 20 * 8);
+        assert_eq!(lines(9), 4);
+        assert_eq!(lines(11), 4);
+
+        let diagnostics = generate(2021, 3, &params).unwrap();
+        let distinct: HashSet<&str> = diagnostics.lines().collect();
+        assert_eq!(distinct.len(), 20);
+        assert!(diagnostics.lines().all(|line| line.len() == 7));
+
+        let crabs = generate(2021, 7, &params).unwrap();
+        let positions: Vec<u64> = crabs
+            .trim()
+            .split(',')
+            .map(|p| p.parse().unwrap())
+            .collect();
+        assert_eq!(positions.len(), 20);
+        assert!(positions.iter().all(|&p| p <= 60));
+
+        let vents = generate(2021, 5, &params).unwrap();
+        for line in vents.lines() {
+            let numbers: Vec<i64> = line
+                .split(" -> ")
+                .flat_map(|point| point.split(','))
+                .map(|n| n.parse().unwrap())
+                .collect();
+            let (dx, dy) = (numbers[2] - numbers[0], numbers[3] - numbers[1]);
+            assert!(dx == 0 || dy == 0 || dx.abs() == dy.abs(), "{}", line);
+            assert!(numbers.iter().all(|&n| (0..=60).contains(&n)));
+        }
+    }
+
+    #[test]
+    fn known_corruption() {
+        let mut rng = StdRng::seed_from_u64(2021);
+        for _ in 0..200 {
+            let (line, chunks) = navigation_line(&mut rng, 30);
+            let mut open: Vec<char> = Vec::new();
+            let mut corrupted = None;
+            for (column, c) in line.chars().enumerate() {
+                match c {
+                    '(' | '[' | '{' | '<' => open.push(c),
+                    _ => {
+                        let opener = match c {
+                            ')' => '(',
+                            ']' => '[',
+                            '}' => '{',
+                            _ => '<',
+                        };
+                        if open.pop() != Some(opener) {
+                            corrupted = Some(Chunks::Corrupted { column, found: c });
+                            break;
+                        }
+                    }
+                }
+            }
+            let found = corrupted.unwrap_or(Chunks::Incomplete {
+                missing: open.len(),
+            });
+            assert_eq!(found, chunks, "{}", line);
+        }
+    }
+
+    #[test]
+    fn impossible_inputs() {
+        let params = |count, width, max| Params {
+            count: Some(count),
+            width: Some(width),
+            max: Some(max),
+            ..Params::default()
+        };
+        assert!(matches!(
+            generate(2021, 3, &params(9, 3, 0)),
+            Err(GenError::Impossible { .. })
+        ));
+        assert!(matches!(
+            generate(2021, 4, &params(1, 5, 24)),
+            Err(GenError::Impossible { .. })
+        ));
+        assert!(matches!(
+            generate(2021, 6, &params(1, 0, 9)),
+            Err(GenError::Impossible { .. })
+        ));
+        assert!(generate(2021, 4, &params(1, 5, 25)).is_ok());
+        assert_eq!(
+            generate(2021, 12, &Params::default()),
+            Err(GenError::UnknownDay { day: 12 })
+        );
+        assert_eq!(
+            generate(2020, 1, &Params::default()),
+            Err(GenError::UnknownYear { year: 2020 })
+        );
+    }
+
+    #[test]
+    fn shrinking() {
+        assert_eq!(10u32.shrink(), vec![0, 5, 9]);
+        assert_eq!((-4i32).shrink(), vec![0, -2, -3, 4]);
+        assert!(0u8.shrink().is_empty());
+        assert!(Vec::<u8>::new().shrink().is_empty());
+        assert_eq!(vec![3u8].shrink(), vec![vec![], vec![0], vec![1], vec![2]]);
+        let rectangle = Rectangle(vec![vec![1u8, 0], vec![0, 0]]);
+        assert!(rectangle.shrink().iter().all(|smaller| {
+            let cols = smaller.0[0].len();
+            smaller.0.iter().all(|row| row.len() == cols)
+        }));
+        assert_eq!(rectangle.shrink().len(), 5);
+    }
+
+    #[test]
+    fn differential() {
+        let numbers = || {
+            inputs(100, 3, |rng| {
+                (0..20).map(|_| rng.gen_range(0..100u32)).collect()
+            })
+        };
+        let sum = |numbers: &Vec<u32>| numbers.iter().sum::<u32>();
+        let checked = Differential::new("sum", sum)
+            .register("reversed", |numbers: &Vec<u32>| numbers.iter().rev().sum())
+            .check(numbers());
+        assert_eq!(checked.unwrap(), 100);
+
+        // Forgets numbers above 50, the smallest input that shows it is a single 51
+        let counterexample = Differential::new("sum", sum)
+            .register("small sum", |numbers: &Vec<u32>| {
+                numbers.iter().filter(|&&n| n <= 50).sum()
+            })
+            .check(numbers())
+            .unwrap_err();
+        assert_eq!(counterexample.solver, "small sum");
+        assert_eq!(counterexample.input, vec![51]);
+        assert_eq!((counterexample.expected, counterexample.found), (51, 0));
+
+        // Inputs that aren't valid are neither checked nor shrunk to
+        let counterexample = Differential::new("sum", sum)
+            .register("small sum", |numbers: &Vec<u32>| {
+                numbers.iter().filter(|&&n| n <= 50).sum()
+            })
+            .only(|numbers| numbers.len() >= 2)
+            .check(numbers())
+            .unwrap_err();
+        assert_eq!(counterexample.input.len(), 2);
+        assert!(counterexample.input.contains(&51));
+    }
+}
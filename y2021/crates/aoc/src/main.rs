@@ -0,0 +1,46 @@
+use aoc::{generate, Params};
+use std::process::ExitCode;
+
+const USAGE: &str =
+    "Usage: aoc gen YEAR DAY [--seed S] [--count N] [--width N] [--height N] [--max N]";
+
+fn number<T: std::str::FromStr>(arg: Option<String>, name: &str) -> Result<T, String> {
+    arg.and_then(|n| n.parse::<T>().ok())
+        .ok_or(format!("{} needs a number", name))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(u32, u32, Params), String> {
+    if args.next().as_deref() != Some("gen") {
+        return Err(USAGE.to_string());
+    }
+    let year = number(args.next(), "YEAR")?;
+    let day = number(args.next(), "DAY")?;
+    let mut params = Params::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => params.seed = number(args.next(), "--seed")?,
+            "--count" => params.count = Some(number(args.next(), "--count")?),
+            "--width" => params.width = Some(number(args.next(), "--width")?),
+            "--height" => params.height = Some(number(args.next(), "--height")?),
+            "--max" => params.max = Some(number(args.next(), "--max")?),
+            x => return Err(format!("Unknown argument: {}\n{}", x, USAGE)),
+        }
+    }
+    Ok((year, day, params))
+}
+
+/// Runs `cargo run -p aoc -- gen 2021 4 --seed 7 --count 3` to print a random puzzle input.
+fn main() -> ExitCode {
+    let generated = parse_args(std::env::args().skip(1))
+        .and_then(|(year, day, params)| generate(year, day, &params).map_err(|e| e.to_string()));
+    match generated {
+        Ok(input) => {
+            print!("{}", input);
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
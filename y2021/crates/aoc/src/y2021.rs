@@ -0,0 +1,257 @@
+//! One generator per day of 2021, each writing the input in the format of the puzzle.
+
+use crate::GenError;
+use rand::seq::index::sample;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+fn impossible(reason: &str) -> GenError {
+    GenError::Impossible {
+        reason: reason.to_string(),
+    }
+}
+
+fn join<T: ToString>(values: &[T], separator: &str) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// Day 1: sonar depths, drifting deeper most of the time.
+pub fn depths(rng: &mut impl Rng, count: usize) -> String {
+    let mut depth: i64 = rng.gen_range(100..200);
+    let mut input = String::new();
+    for _ in 0..count {
+        input.push_str(&format!("{}\n", depth));
+        depth = (depth + rng.gen_range(-10..=30)).max(0);
+    }
+    input
+}
+
+/// Day 2: `forward`, `down` and `up` commands that never take the submarine above the surface.
+pub fn commands(rng: &mut impl Rng, count: usize, max: u64) -> String {
+    let max = max.max(1);
+    let mut depth = 0;
+    let mut input = String::new();
+    for _ in 0..count {
+        let amount = rng.gen_range(1..=max);
+        let direction = match rng.gen_range(0..3) {
+            0 => "forward",
+            1 => "down",
+            _ if depth >= amount => "up",
+            _ => "down",
+        };
+        match direction {
+            "down" => depth += amount,
+            "up" => depth -= amount,
+            _ => {}
+        }
+        input.push_str(&format!("{} {}\n", direction, amount));
+    }
+    input
+}
+
+/// Day 3: distinct binary numbers of `width` bits, so the life support rating always narrows
+/// down to a single number.
+pub fn diagnostics(rng: &mut impl Rng, count: usize, width: usize) -> Result<String, GenError> {
+    if width == 0 || width > 32 {
+        return Err(impossible("diagnostics need 1 to 32 bits"));
+    }
+    if count as u64 > 1 << width {
+        return Err(impossible("more diagnostics than numbers of that width"));
+    }
+    let numbers = sample(rng, 1 << width, count);
+    Ok(numbers
+        .iter()
+        .map(|number| format!("{:0width$b}\n", number, width = width))
+        .collect())
+}
+
+/// Day 4: draws of every number below `max` and `count` boards of `size` by `size`, every
+/// board with distinct numbers.
+pub fn bingo(rng: &mut impl Rng, count: usize, size: usize, max: u64) -> Result<String, GenError> {
+    let numbers = max as usize;
+    if size == 0 || size * size > numbers {
+        return Err(impossible("a board needs more numbers than can be drawn"));
+    }
+    let mut draws: Vec<usize> = (0..numbers).collect();
+    draws.shuffle(rng);
+    let mut input = format!("{}\n", join(&draws, ","));
+    let cell = (numbers - 1).to_string().len();
+    for _ in 0..count {
+        input.push('\n');
+        let board = sample(rng, numbers, size * size).into_vec();
+        for row in board.chunks(size) {
+            let row: Vec<String> = row
+                .iter()
+                .map(|number| format!("{:>cell$}", number, cell = cell))
+                .collect();
+            input.push_str(&format!("{}\n", row.join(" ")));
+        }
+    }
+    Ok(input)
+}
+
+/// Day 5: horizontal, vertical and diagonal lines of vents with coordinates up to `max`.
+pub fn vents(rng: &mut impl Rng, count: usize, max: u64) -> String {
+    let mut input = String::new();
+    for _ in 0..count {
+        let (x1, y1) = (rng.gen_range(0..=max), rng.gen_range(0..=max));
+        let (x2, y2) = match rng.gen_range(0..3) {
+            0 => (rng.gen_range(0..=max), y1),
+            1 => (x1, rng.gen_range(0..=max)),
+            _ => {
+                // A diagonal can only be as long as the room to the nearest edge allows
+                let length = rng.gen_range(0..=max);
+                let right = rng.gen_bool(0.5);
+                let down = rng.gen_bool(0.5);
+                let room_x = if right { max - x1 } else { x1 };
+                let room_y = if down { max - y1 } else { y1 };
+                let length = length.min(room_x).min(room_y);
+                let x2 = if right { x1 + length } else { x1 - length };
+                let y2 = if down { y1 + length } else { y1 - length };
+                (x2, y2)
+            }
+        };
+        input.push_str(&format!("{},{} -> {},{}\n", x1, y1, x2, y2));
+    }
+    input
+}
+
+/// Day 6: the timers of `count` lanternfish, from 1 up to `max`.
+pub fn fish(rng: &mut impl Rng, count: usize, max: u64) -> Result<String, GenError> {
+    if max > 8 {
+        return Err(impossible("lanternfish timers go up to 8"));
+    }
+    let timers: Vec<u64> = (0..count).map(|_| rng.gen_range(1..=max.max(1))).collect();
+    Ok(format!("{}\n", join(&timers, ",")))
+}
+
+/// Day 7: the positions of `count` crabs up to `max`.
+pub fn crabs(rng: &mut impl Rng, count: usize, max: u64) -> String {
+    let positions: Vec<u64> = (0..count).map(|_| rng.gen_range(0..=max)).collect();
+    format!("{}\n", join(&positions, ","))
+}
+
+const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+/// Day 8: `count` displays, each with its own scrambled wiring, its ten patterns in random
+/// order and four digits on the display.
+pub fn displays(rng: &mut impl Rng, count: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..count {
+        let mut wires: Vec<char> = "abcdefg".chars().collect();
+        wires.shuffle(rng);
+        let scramble = |digit: usize, rng: &mut dyn rand::RngCore| {
+            let mut letters: Vec<char> = DIGITS[digit]
+                .chars()
+                .map(|segment| wires[(segment as u8 - b'a') as usize])
+                .collect();
+            letters.shuffle(rng);
+            letters.into_iter().collect::<String>()
+        };
+        let mut order: Vec<usize> = (0..10).collect();
+        order.shuffle(rng);
+        let patterns: Vec<String> = order.iter().map(|&digit| scramble(digit, rng)).collect();
+        let shown: Vec<String> = (0..4)
+            .map(|_| {
+                let digit = rng.gen_range(0..10);
+                scramble(digit, rng)
+            })
+            .collect();
+        input.push_str(&format!("{} | {}\n", patterns.join(" "), shown.join(" ")));
+    }
+    input
+}
+
+fn digit_grid(rng: &mut impl Rng, height: usize, width: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..height {
+        let row: String = (0..width)
+            .map(|_| char::from(b'0' + rng.gen_range(0..10u8)))
+            .collect();
+        input.push_str(&format!("{}\n", row));
+    }
+    input
+}
+
+/// Day 9: a heightmap of `height` rows of `width` digits.
+pub fn heightmap(rng: &mut impl Rng, height: usize, width: usize) -> Result<String, GenError> {
+    if height == 0 || width == 0 {
+        return Err(impossible("a heightmap needs at least one location"));
+    }
+    Ok(digit_grid(rng, height, width))
+}
+
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// What a navigation line was generated to be.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Chunks {
+    /// Illegal from this character on, counted from 0.
+    Corrupted { column: usize, found: char },
+    /// Missing this many closers at the end.
+    Incomplete { missing: usize },
+}
+
+/// Day 10: a navigation line of about `width` brackets and what is wrong with it.
+pub fn navigation_line(rng: &mut impl Rng, width: usize) -> (String, Chunks) {
+    let mut line = String::new();
+    let mut open: Vec<usize> = Vec::new();
+    let corrupt = rng.gen_bool(0.5);
+    for _ in 0..width.max(1) {
+        if open.is_empty() || rng.gen_bool(0.55) {
+            let pair = rng.gen_range(0..BRACKETS.len());
+            open.push(pair);
+            line.push(BRACKETS[pair].0);
+        } else if let Some(pair) = open.pop() {
+            line.push(BRACKETS[pair].1);
+        }
+    }
+    if corrupt {
+        // Close the innermost chunk with any other closer
+        let column = line.chars().count();
+        let innermost = open.last().copied();
+        let others: Vec<usize> = (0..BRACKETS.len())
+            .filter(|&pair| Some(pair) != innermost)
+            .collect();
+        let found = BRACKETS[*others.choose(rng).unwrap()].1;
+        line.push(found);
+        for _ in 0..rng.gen_range(0..10) {
+            let pair = BRACKETS.choose(rng).unwrap();
+            line.push(if rng.gen_bool(0.5) { pair.0 } else { pair.1 });
+        }
+        (line, Chunks::Corrupted { column, found })
+    } else {
+        if open.is_empty() {
+            let pair = rng.gen_range(0..BRACKETS.len());
+            open.push(pair);
+            line.push(BRACKETS[pair].0);
+        }
+        (
+            line,
+            Chunks::Incomplete {
+                missing: open.len(),
+            },
+        )
+    }
+}
+
+/// Day 10: `count` navigation lines, about half corrupted and the others incomplete.
+pub fn navigation(rng: &mut impl Rng, count: usize, width: usize) -> String {
+    (0..count)
+        .map(|_| format!("{}\n", navigation_line(rng, width).0))
+        .collect()
+}
+
+/// Day 11: the energy levels of `height` rows of `width` octopuses.
+pub fn octopuses(rng: &mut impl Rng, height: usize, width: usize) -> Result<String, GenError> {
+    if height == 0 || width == 0 {
+        return Err(impossible("a cavern needs at least one octopus"));
+    }
+    Ok(digit_grid(rng, height, width))
+}
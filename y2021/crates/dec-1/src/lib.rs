@@ -0,0 +1,60 @@
+//! The depth measurements of the sonar sweep, one per line.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    InvalidDepth { line: usize, value: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidDepth { line, value } => {
+                write!(f, "Line {}: {:?} is not a depth", line, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// One depth per line, blank lines are skipped.
+pub fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line, value)| {
+            value.parse::<i32>().map_err(|_| ParseError::InvalidDepth {
+                line,
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_input("199\r\n200\n\n208 \n"), Ok(vec![199, 200, 208]));
+        assert_eq!(
+            parse_input("199\n2OO\n"),
+            Err(ParseError::InvalidDepth {
+                line: 2,
+                value: "2OO".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("99999999999"),
+            Err(ParseError::InvalidDepth {
+                line: 1,
+                value: "99999999999".to_string()
+            })
+        );
+    }
+}
@@ -1,7 +1,7 @@
+use dec_1::parse_input;
 use itertools::Itertools;
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::BufRead;
 use std::path::Path;
 
 fn main() {
@@ -40,10 +40,6 @@ fn read_input<P>(filename: P) -> io::Result<Vec<i32>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
-    let x: Vec<i32> = io::BufReader::new(file)
-        .lines()
-        .map(|l| l.unwrap().parse::<i32>().unwrap())
-        .collect();
-    Ok(x)
+    let input = fs::read_to_string(filename)?;
+    parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
 }
@@ -0,0 +1,236 @@
+use crate::lint::{Diagnostic, Problem};
+use crate::{check_pairs, parse_table, TableError};
+use num_bigint::BigUint;
+use std::str::FromStr;
+
+/// An opening and a closing token with the scores of part 1 and part 2.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Pair {
+    open: String,
+    close: String,
+    /// Scored when the closer shows up where another one was expected.
+    corruption: u64,
+    /// Scored for every missing closer of this pair.
+    completion: u64,
+}
+
+const BRACKETS: [(&str, &str, u64, u64); 4] = [
+    ("(", ")", 3, 1),
+    ("[", "]", 57, 2),
+    ("{", "}", 1197, 3),
+    ("<", ">", 25137, 4),
+];
+
+/// What a line turned out to be.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Check {
+    /// Every opener was closed in the right order.
+    Balanced,
+    /// The line stops before all openers are closed. Every missing closer multiplies the score
+    /// by five, so it grows without bounds.
+    Incomplete { completion: String, score: BigUint },
+    /// The first token at `column` (counted in characters) that does not fit.
+    Corrupted {
+        column: usize,
+        found: String,
+        /// The closer that would have fitted, none when nothing was open.
+        expected: Option<String>,
+        score: u64,
+    },
+    /// A character at `column` that is not part of any delimiter.
+    InvalidInput { column: usize, found: char },
+}
+
+enum Token {
+    Open(usize),
+    Close(usize),
+}
+
+/// The table of delimiter pairs a line is checked against.
+#[derive(Debug, Clone)]
+pub struct Delimiters {
+    pairs: Vec<Pair>,
+    /// Characters outside of the table are text between the delimiters instead of errors.
+    skip_text: bool,
+}
+
+impl Delimiters {
+    pub fn new(pairs: &[(&str, &str, u64, u64)]) -> Result<Delimiters, TableError> {
+        check_pairs(pairs)?;
+        Ok(Delimiters {
+            pairs: pairs
+                .iter()
+                .map(|&(open, close, corruption, completion)| Pair {
+                    open: open.to_string(),
+                    close: close.to_string(),
+                    corruption,
+                    completion,
+                })
+                .collect(),
+            skip_text: false,
+        })
+    }
+
+    /// The four brackets of the navigation subsystem.
+    pub fn brackets() -> Delimiters {
+        Delimiters::new(&BRACKETS).unwrap()
+    }
+
+    pub fn skipping_text(mut self) -> Delimiters {
+        self.skip_text = true;
+        self
+    }
+
+    /// The longest token `rest` starts with. A token opening and closing the same pair
+    /// closes it when it is the innermost one open.
+    fn token(&self, rest: &str, innermost: Option<usize>) -> Option<(Token, usize)> {
+        let mut best: Option<(Token, usize)> = None;
+        for (index, pair) in self.pairs.iter().enumerate() {
+            let candidates = [
+                (Token::Close(index), &pair.close),
+                (Token::Open(index), &pair.open),
+            ];
+            for (token, text) in candidates {
+                let closes_innermost = innermost == Some(index);
+                if matches!(token, Token::Close(_)) && pair.open == pair.close && !closes_innermost
+                {
+                    continue;
+                }
+                if rest.starts_with(text.as_str())
+                    && best.as_ref().is_none_or(|(_, length)| text.len() > *length)
+                {
+                    best = Some((token, text.len()));
+                }
+            }
+        }
+        best
+    }
+
+    pub fn check(&self, line: &str) -> Check {
+        let mut open: Vec<usize> = Vec::new();
+        let mut rest = line;
+        let mut column = 0;
+        while let Some(c) = rest.chars().next() {
+            let length = match self.token(rest, open.last().copied()) {
+                Some((Token::Open(pair), length)) => {
+                    open.push(pair);
+                    length
+                }
+                Some((Token::Close(pair), length)) => {
+                    let innermost = open.pop();
+                    if innermost != Some(pair) {
+                        return Check::Corrupted {
+                            column,
+                            found: self.pairs[pair].close.clone(),
+                            expected: innermost.map(|i| self.pairs[i].close.clone()),
+                            score: self.pairs[pair].corruption,
+                        };
+                    }
+                    length
+                }
+                None if self.skip_text => c.len_utf8(),
+                None => return Check::InvalidInput { column, found: c },
+            };
+            column += rest[..length].chars().count();
+            rest = &rest[length..];
+        }
+
+        if open.is_empty() {
+            return Check::Balanced;
+        }
+        let missing: Vec<&Pair> = open.iter().rev().map(|&i| &self.pairs[i]).collect();
+        Check::Incomplete {
+            completion: missing.iter().map(|pair| pair.close.as_str()).collect(),
+            score: missing.iter().fold(BigUint::default(), |score, pair| {
+                score * 5u32 + pair.completion
+            }),
+        }
+    }
+
+    /// Every problem on the line. After a mismatch the checker carries on as if the missing
+    /// closers were there, or as if an unexpected closer wasn't.
+    pub fn lint(&self, line: &str) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        // The open pairs with the column of their opener
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        let mut rest = line;
+        let mut column = 0;
+        while let Some(c) = rest.chars().next() {
+            let (length, width) = match self.token(rest, open.last().map(|&(pair, _)| pair)) {
+                Some((token, length)) => {
+                    let width = rest[..length].chars().count();
+                    match token {
+                        Token::Open(pair) => open.push((pair, column)),
+                        Token::Close(pair) => {
+                            match open.iter().rposition(|&(other, _)| other == pair) {
+                                Some(depth) => {
+                                    for &(inner, opened) in open[depth + 1..].iter().rev() {
+                                        diagnostics.push(Diagnostic {
+                                            column,
+                                            width,
+                                            problem: Problem::Missing {
+                                                closer: self.pairs[inner].close.clone(),
+                                                opened,
+                                            },
+                                        });
+                                    }
+                                    open.truncate(depth);
+                                }
+                                None => diagnostics.push(Diagnostic {
+                                    column,
+                                    width,
+                                    problem: Problem::Unexpected {
+                                        found: self.pairs[pair].close.clone(),
+                                        expected: open
+                                            .last()
+                                            .map(|&(inner, _)| self.pairs[inner].close.clone()),
+                                    },
+                                }),
+                            }
+                        }
+                    }
+                    (length, width)
+                }
+                None => {
+                    if !self.skip_text {
+                        diagnostics.push(Diagnostic {
+                            column,
+                            width: 1,
+                            problem: Problem::Unknown(c),
+                        });
+                    }
+                    (c.len_utf8(), 1)
+                }
+            };
+            column += width;
+            rest = &rest[length..];
+        }
+
+        for &(pair, opened) in open.iter().rev() {
+            diagnostics.push(Diagnostic {
+                column,
+                width: 1,
+                problem: Problem::Missing {
+                    closer: self.pairs[pair].close.clone(),
+                    opened,
+                },
+            });
+        }
+        diagnostics
+    }
+}
+
+/// Reads a table as `parse_table` does.
+impl FromStr for Delimiters {
+    type Err = TableError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table = parse_table(s)?;
+        let delimiters = Delimiters::new(&table.pairs)?;
+        Ok(if table.skip_text {
+            delimiters.skipping_text()
+        } else {
+            delimiters
+        })
+    }
+}
@@ -0,0 +1,123 @@
+//! The delimiter table the navigation lines are checked against, and the checker itself.
+
+pub mod delimiters;
+pub mod lint;
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TableError {
+    InvalidLine { line: usize },
+    EmptyToken { pair: usize },
+    SameToken { token: String },
+    Empty,
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::InvalidLine { line } => write!(
+                f,
+                "Line {}: expected an opener, a closer and their two scores",
+                line
+            ),
+            TableError::EmptyToken { pair } => write!(f, "Pair {} has an empty token", pair),
+            TableError::SameToken { token } => write!(f, "{} is used by two pairs", token),
+            TableError::Empty => write!(f, "No delimiters"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+/// The pairs `(open, close, corruption, completion)` of a table as written.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Table<'a> {
+    pub pairs: Vec<(&'a str, &'a str, u64, u64)>,
+    /// Characters outside of the table are text between the delimiters instead of errors.
+    pub skip_text: bool,
+}
+
+/// At least one pair, and no token empty or used by two pairs.
+pub fn check_pairs(pairs: &[(&str, &str, u64, u64)]) -> Result<(), TableError> {
+    if pairs.is_empty() {
+        return Err(TableError::Empty);
+    }
+    let mut tokens: Vec<&str> = Vec::new();
+    for (index, &(open, close, _, _)) in pairs.iter().enumerate() {
+        if open.is_empty() || close.is_empty() {
+            return Err(TableError::EmptyToken { pair: index + 1 });
+        }
+        // A pair may open and close with the same token, like quotes do
+        let own = if open == close {
+            vec![open]
+        } else {
+            vec![open, close]
+        };
+        for token in own {
+            if tokens.contains(&token) {
+                return Err(TableError::SameToken {
+                    token: token.to_string(),
+                });
+            }
+            tokens.push(token);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a table with one pair per line, `open close corruption completion`, and `#` comments.
+/// A line `skip-text` lets other characters pass as text between the delimiters.
+pub fn parse_table(input: &str) -> Result<Table<'_>, TableError> {
+    let mut pairs: Vec<(&str, &str, u64, u64)> = Vec::new();
+    let mut skip_text = false;
+    for (line, text) in input.lines().enumerate() {
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        if text == "skip-text" {
+            skip_text = true;
+            continue;
+        }
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        let pair = match parts[..] {
+            [open, close, corruption, completion] => corruption
+                .parse::<u64>()
+                .ok()
+                .zip(completion.parse::<u64>().ok())
+                .map(|(corruption, completion)| (open, close, corruption, completion)),
+            _ => None,
+        };
+        pairs.push(pair.ok_or(TableError::InvalidLine { line: line + 1 })?);
+    }
+    check_pairs(&pairs)?;
+    Ok(Table { pairs, skip_text })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_table, Table, TableError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_table("# quotes\r\n\" \" 5 1\n\nskip-text\n"),
+            Ok(Table {
+                pairs: vec![("\"", "\"", 5, 1)],
+                skip_text: true
+            })
+        );
+        assert_eq!(
+            parse_table("( ) 3 1\n[ ] x 2"),
+            Err(TableError::InvalidLine { line: 2 })
+        );
+        assert_eq!(
+            parse_table("( ) 3 1\n) ( 1 1"),
+            Err(TableError::SameToken {
+                token: ")".to_string()
+            })
+        );
+        assert_eq!(parse_table("# nothing\n"), Err(TableError::Empty));
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Problem {
+    /// A closer while no opener of its pair is open.
+    Unexpected {
+        found: String,
+        expected: Option<String>,
+    },
+    /// An opener that is never closed, its closer is assumed where it had to be.
+    Missing { closer: String, opened: usize },
+    /// A character outside of the delimiter table.
+    Unknown(char),
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Unexpected {
+                found,
+                expected: Some(expected),
+            } => write!(f, "expected `{}`, found `{}`", expected, found),
+            Problem::Unexpected {
+                found,
+                expected: None,
+            } => write!(f, "unexpected `{}`, nothing is open", found),
+            Problem::Missing { closer, opened } => write!(
+                f,
+                "missing `{}` to close the opener at column {}",
+                closer,
+                opened + 1
+            ),
+            Problem::Unknown(c) => write!(f, "unknown character `{}`", c),
+        }
+    }
+}
+
+/// A problem found at `column`, counted in characters, spanning `width` characters.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub column: usize,
+    pub width: usize,
+    pub problem: Problem,
+}
+
+impl Diagnostic {
+    /// The diagnostic with the line it was found on and carets pointing at the problem.
+    pub fn render(&self, number: usize, line: &str) -> String {
+        let gutter = " ".repeat(number.to_string().len());
+        format!(
+            "error: {}\n{} --> line {}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self.problem,
+            gutter,
+            number,
+            self.column + 1,
+            gutter,
+            number,
+            line,
+            gutter,
+            " ".repeat(self.column),
+            "^".repeat(self.width.max(1))
+        )
+    }
+}
@@ -1,97 +1,107 @@
+use dec_10::delimiters::{Check, Delimiters};
+use num_bigint::BigUint;
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-enum Score {
-    Error(i32),
-    Complete(u64),
+/// Checks every line once, both parts are scored from the results.
+fn check_lines(lines: &[String], delimiters: &Delimiters) -> Vec<Check> {
+    lines.iter().map(|line| delimiters.check(line)).collect()
 }
 
-fn check_format(chunks: String) -> Score {
-    let mut chars: Vec<char> = Vec::new();
+/// Part 1: the scores of the first illegal character of every corrupted line summed. A sum of
+/// `u64` scores only overflows a `u128` after 2^64 lines.
+fn error_score(checks: &[Check]) -> u128 {
+    checks
+        .iter()
+        .map(|check| match check {
+            Check::Corrupted { score, .. } => *score as u128,
+            _ => 0,
+        })
+        .sum()
+}
 
-    for c in chunks.chars() {
-        match c {
-            '(' => {
-                chars.push(c);
-            }
-            ')' => {
-                let last = chars.pop().unwrap();
-                if !last.eq(&'(') {
-                    println!("Expected: ( got: {}", last);
-                    return Score::Error(3);
-                }
-            }
-            '[' => {
-                chars.push(c);
-            }
-            ']' => {
-                let last = chars.pop().unwrap();
-                if !last.eq(&'[') {
-                    println!("Expected: [ got: {}", last);
-                    return Score::Error(57);
-                }
-            }
-            '{' => {
-                chars.push(c);
-            }
-            '}' => {
-                let last = chars.pop().unwrap();
-                if !last.eq(&'{') {
-                    println!("Expected: {{ got: {}", last);
-                    return Score::Error(1197);
-                }
-            }
-            '<' => {
-                chars.push(c);
-            }
-            '>' => {
-                let last = chars.pop().unwrap();
-                if !last.eq(&'<') {
-                    println!("Expected: < got: {}", last);
-                    return Score::Error(25137);
-                }
-            }
-            x => {
-                println!("Did not expect this: {}", x);
-                return Score::Error(0);
-            }
-        }
-    }
-    let mut score = 0u64;
-    chars.reverse();
-    for c in chars {
-        score *= 5;
-        match c {
-            '(' => score += 1,
-            '[' => score += 2,
-            '{' => score += 3,
-            '<' => score += 4,
-            x => {
-                println!("Did not expect this: {}", x)
+/// Part 2: the middle score of completing the incomplete lines. With an even number of lines the
+/// lower of the two middle scores is taken.
+fn complete_score(checks: &[Check]) -> Option<BigUint> {
+    let mut complete_scores: Vec<&BigUint> = checks
+        .iter()
+        .filter_map(|check| match check {
+            Check::Incomplete { score, .. } => Some(score),
+            _ => None,
+        })
+        .collect();
+    complete_scores.sort_unstable();
+    let middle = complete_scores.len().checked_sub(1)? / 2;
+    Some(complete_scores.swap_remove(middle).clone())
+}
+
+/// Runs `cargo run -p dec-10 -- [--delimiters FILE] [--lint]` to check other delimiters than
+/// the four brackets, see `Delimiters::from_str` for the file format, or to list every error.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut delimiters = Delimiters::brackets();
+    let mut lint = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--delimiters" => {
+                let file = args.next().expect("--delimiters needs a file");
+                let table = std::fs::read_to_string(file).expect("Could not read the delimiters");
+                delimiters = table
+                    .parse::<Delimiters>()
+                    .unwrap_or_else(|e| panic!("{}", e));
             }
+            "--lint" => lint = true,
+            x => panic!("Unknown argument: {}", x),
         }
     }
-    Score::Complete(score)
-}
 
-fn main() {
     if let Ok(lines) = read_input("./resources/input-dec-10") {
-        let mut score = 0;
-        let mut complete_scores: Vec<u64> = Vec::new();
-        for line in lines {
-            match check_format(line) {
-                Score::Error(s) => score += s,
-                Score::Complete(s) => complete_scores.push(s),
+        if lint {
+            for (number, line) in lines.iter().enumerate() {
+                for diagnostic in delimiters.lint(line) {
+                    println!("{}", diagnostic.render(number + 1, line));
+                }
             }
         }
-        println!("Error score: {}", score);
-        complete_scores.sort_unstable();
-        println!(
-            "Complete score: {}",
-            complete_scores.get(complete_scores.len() / 2).unwrap()
-        );
+        let checks = check_lines(&lines, &delimiters);
+        for (number, check) in checks.iter().enumerate() {
+            match check {
+                Check::InvalidInput { column, found } => println!(
+                    "Line {}:{}: not a delimiter: {}",
+                    number + 1,
+                    column + 1,
+                    found
+                ),
+                Check::Corrupted {
+                    column,
+                    found,
+                    expected,
+                    ..
+                } => match expected {
+                    Some(expected) => println!(
+                        "Line {}:{}: expected {} got: {}",
+                        number + 1,
+                        column + 1,
+                        expected,
+                        found
+                    ),
+                    None => println!(
+                        "Line {}:{}: nothing to close with {}",
+                        number + 1,
+                        column + 1,
+                        found
+                    ),
+                },
+                Check::Balanced | Check::Incomplete { .. } => {}
+            }
+        }
+        println!("Error score: {}", error_score(&checks));
+        match complete_score(&checks) {
+            Some(score) => println!("Complete score: {}", score),
+            None => println!("No incomplete lines"),
+        }
     }
 }
 
@@ -114,38 +124,284 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{check_format, read_input, Score};
+    use crate::{check_lines, complete_score, error_score, read_input};
+    use aoc::differential::{inputs, Differential};
+    use aoc::y2021::navigation_line;
+    use dec_10::delimiters::{Check, Delimiters};
+    use dec_10::lint::{Diagnostic, Problem};
+    use dec_10::TableError;
+    use num_bigint::BigUint;
+    use rand::Rng;
 
     #[test]
     fn solution_1() {
-        if let Ok(lines) = read_input("../../test/resources/input-dec-10") {
-            let mut error_score = 0;
-            let mut complete_scores: Vec<u64> = Vec::new();
-            for line in lines {
-                match check_format(line) {
-                    Score::Error(s) => error_score += s,
-                    Score::Complete(s) => complete_scores.push(s),
+        let lines = read_input("../../resources/test-input-dec-10").unwrap();
+        let checks = check_lines(&lines, &Delimiters::brackets());
+        assert_eq!(error_score(&checks), 26397);
+    }
+
+    #[test]
+    fn solution_2() {
+        let lines = read_input("../../resources/test-input-dec-10").unwrap();
+        let delimiters = Delimiters::brackets();
+        let checks = check_lines(&lines, &delimiters);
+        assert_eq!(error_score(&checks), 26397);
+        assert_eq!(complete_score(&checks), Some(BigUint::from(288957u32)));
+        assert_eq!(
+            delimiters.check(&lines[0]),
+            Check::Incomplete {
+                completion: "}}]])})]".to_string(),
+                score: BigUint::from(288957u32)
+            }
+        );
+        assert_eq!(
+            delimiters.check(&lines[2]),
+            Check::Corrupted {
+                column: 12,
+                found: "}".to_string(),
+                expected: Some("]".to_string()),
+                score: 1197
+            }
+        );
+    }
+
+    #[test]
+    fn leading_closer() {
+        let delimiters = Delimiters::brackets();
+        assert_eq!(
+            delimiters.check(")()"),
+            Check::Corrupted {
+                column: 0,
+                found: ")".to_string(),
+                expected: None,
+                score: 3
+            }
+        );
+        assert_eq!(delimiters.check("([]<>)"), Check::Balanced);
+        assert_eq!(
+            delimiters.check("(x)"),
+            Check::InvalidInput {
+                column: 1,
+                found: 'x'
+            }
+        );
+    }
+
+    #[test]
+    fn other_alphabets() {
+        let quotes = Delimiters::new(&[("\"", "\"", 2, 2), ("'", "'", 1, 1), ("(", ")", 3, 3)])
+            .unwrap()
+            .skipping_text();
+        assert_eq!(quotes.check("say \"a 'b' c\" (ok)"), Check::Balanced);
+        assert_eq!(
+            quotes.check("(\"a)\""),
+            Check::Corrupted {
+                column: 3,
+                found: ")".to_string(),
+                expected: Some("\"".to_string()),
+                score: 3
+            }
+        );
+
+        let tags: Delimiters = "# Markup\nskip-text\n<b> </b> 5 1\n<i> </i> 7 2\n<p> </p> 11 3\n"
+            .parse()
+            .unwrap();
+        assert_eq!(tags.check("<p>Some <b>bold</b> text</p>"), Check::Balanced);
+        assert_eq!(
+            tags.check("<p>é <b><i>x</b>"),
+            Check::Corrupted {
+                column: 12,
+                found: "</b>".to_string(),
+                expected: Some("</i>".to_string()),
+                score: 5
+            }
+        );
+        assert_eq!(
+            tags.check("<p><i>open"),
+            Check::Incomplete {
+                completion: "</i></p>".to_string(),
+                score: BigUint::from(13u32)
+            }
+        );
+
+        assert_eq!(
+            "( ) 1".parse::<Delimiters>().unwrap_err(),
+            TableError::InvalidLine { line: 1 }
+        );
+        assert_eq!(
+            Delimiters::new(&[("(", ")", 1, 1), ("[", ")", 2, 2)]).unwrap_err(),
+            TableError::SameToken {
+                token: ")".to_string()
+            }
+        );
+        assert_eq!(Delimiters::new(&[]).unwrap_err(), TableError::Empty);
+    }
+
+    #[test]
+    fn lint() {
+        let lines = read_input("../../resources/test-input-dec-10").unwrap();
+        let delimiters = Delimiters::brackets();
+        // The first error is the one part 1 scores
+        for line in lines.iter() {
+            if let Check::Corrupted {
+                column,
+                found,
+                expected,
+                ..
+            } = delimiters.check(line)
+            {
+                let first = &delimiters.lint(line)[0];
+                assert_eq!(first.column, column);
+                match &first.problem {
+                    Problem::Missing { closer, .. } => assert_eq!(Some(closer), expected.as_ref()),
+                    Problem::Unexpected { found: other, .. } => assert_eq!(other, &found),
+                    Problem::Unknown(_) => panic!("Only brackets in the example"),
                 }
             }
-            assert_eq!(error_score, 26397);
         }
+
+        assert_eq!(
+            delimiters.lint("(]x)<"),
+            vec![
+                Diagnostic {
+                    column: 1,
+                    width: 1,
+                    problem: Problem::Unexpected {
+                        found: "]".to_string(),
+                        expected: Some(")".to_string())
+                    }
+                },
+                Diagnostic {
+                    column: 2,
+                    width: 1,
+                    problem: Problem::Unknown('x')
+                },
+                Diagnostic {
+                    column: 5,
+                    width: 1,
+                    problem: Problem::Missing {
+                        closer: ">".to_string(),
+                        opened: 4
+                    }
+                },
+            ]
+        );
+        // Closing the outer pair closes the inner ones that are still open too
+        let errors = delimiters.lint("{([<}[]");
+        let missing: Vec<(usize, &str)> = errors
+            .iter()
+            .map(|diagnostic| match &diagnostic.problem {
+                Problem::Missing { closer, .. } => (diagnostic.column, closer.as_str()),
+                _ => panic!("Only missing closers expected"),
+            })
+            .collect();
+        assert_eq!(missing, vec![(4, ">"), (4, "]"), (4, ")")]);
+        assert!(delimiters.lint("[<>({}){}[([])<>]]").is_empty());
     }
 
     #[test]
-    fn solution_2() {
-        if let Ok(lines) = read_input("../../test/resources/input-dec-10") {
-            let mut error_score = 0;
-            let mut complete_scores: Vec<u64> = Vec::new();
-            for line in lines {
-                match check_format(line) {
-                    Score::Error(s) => error_score += s,
-                    Score::Complete(s) => complete_scores.push(s),
-                }
+    fn render() {
+        let delimiters = "skip-text\n<b> </b> 1 1\n<i> </i> 2 2"
+            .parse::<Delimiters>()
+            .unwrap();
+        let line = "<b>bold <i>both</b>";
+        let errors = delimiters.lint(line);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].render(12, line),
+            "error: missing `</i>` to close the opener at column 9\n   --> line 12:16\n   |\n12 | <b>bold <i>both</b>\n   |                ^^^^\n"
+        );
+    }
+
+    #[test]
+    fn large_scores() {
+        let delimiters = Delimiters::brackets();
+        // 4 * (5^39 + ... + 5 + 1) = 5^40 - 1 does not fit in a u64 any more
+        let line = "<".repeat(40);
+        let expected = BigUint::from(5u32).pow(40) - 1u32;
+        match delimiters.check(&line) {
+            Check::Incomplete { completion, score } => {
+                assert_eq!(completion, ">".repeat(40));
+                assert_eq!(score, expected);
             }
-            complete_scores.sort_unstable();
-            let complete_scores = *complete_scores.get(complete_scores.len() / 2).unwrap();
-            assert_eq!(error_score, 26397);
-            assert_eq!(complete_scores, 288957u64);
+            other => panic!("Expected an incomplete line, got {:?}", other),
         }
+
+        let big = Delimiters::new(&[("(", ")", u64::MAX, 1)]).unwrap();
+        let lines: Vec<String> = vec![")".to_string(); 3];
+        assert_eq!(
+            error_score(&check_lines(&lines, &big)),
+            u64::MAX as u128 * 3
+        );
+    }
+
+    #[test]
+    fn even_median() {
+        let delimiters = Delimiters::brackets();
+        let lines: Vec<String> = ["(", "[", "{{", "<", "x", "()"]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        let checks = check_lines(&lines, &delimiters);
+        // Scores 1, 2, 18 and 4, the valid, invalid and balanced lines don't count
+        assert_eq!(complete_score(&checks), Some(BigUint::from(2u32)));
+        assert_eq!(complete_score(&checks[4..]), None);
+        assert_eq!(error_score(&checks), 0);
+    }
+
+    /// What is wrong with a line, as far as both the checker and the linter can tell.
+    #[derive(Debug, Eq, PartialEq)]
+    enum Verdict {
+        Balanced,
+        Incomplete(String),
+        Corrupted { column: usize, found: char },
+        Invalid { column: usize },
+    }
+
+    #[test]
+    fn differential() {
+        let delimiters = Delimiters::brackets();
+        Differential::new("check", |line: &String| match delimiters.check(line) {
+            Check::Balanced => Verdict::Balanced,
+            Check::Incomplete { completion, .. } => Verdict::Incomplete(completion),
+            Check::Corrupted { column, found, .. } => Verdict::Corrupted {
+                column,
+                found: found.chars().next().unwrap(),
+            },
+            Check::InvalidInput { column, .. } => Verdict::Invalid { column },
+        })
+        .register("lint", |line| {
+            let diagnostics = delimiters.lint(line);
+            let Some(first) = diagnostics.first() else {
+                return Verdict::Balanced;
+            };
+            let end = line.chars().count();
+            match first.problem {
+                Problem::Missing { .. } if first.column == end => Verdict::Incomplete(
+                    diagnostics
+                        .iter()
+                        .map(|diagnostic| match &diagnostic.problem {
+                            Problem::Missing { closer, .. } => closer.as_str(),
+                            _ => "",
+                        })
+                        .collect(),
+                ),
+                Problem::Missing { .. } | Problem::Unexpected { .. } => Verdict::Corrupted {
+                    column: first.column,
+                    found: line.chars().nth(first.column).unwrap(),
+                },
+                Problem::Unknown(_) => Verdict::Invalid {
+                    column: first.column,
+                },
+            }
+        })
+        .assert(inputs(300, 10, |rng| {
+            let (mut line, _) = navigation_line(rng, 30);
+            if rng.gen_bool(0.1) {
+                let column = rng.gen_range(0..=line.len());
+                line.insert(column, 'x');
+            }
+            line
+        }));
     }
 }
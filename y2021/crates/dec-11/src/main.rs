@@ -1,43 +1,21 @@
+mod rules;
+mod run;
+
+use crate::rules::{Neighbourhood, Rules};
+use crate::run::{run, Stop};
 use anstyle::Style;
+use digits::parse_digits;
 use grid::Grid;
+use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-enum Synced {
-    No { flashes: u32 },
-    Yes { flashes: u32, synced_step: u32 },
-}
-
-impl Synced {
-    fn flashes(&self) -> u32 {
-        match self {
-            Synced::No { flashes } => *flashes,
-            Synced::Yes {
-                flashes,
-                synced_step: _,
-            } => *flashes,
-        }
-    }
-
-    fn step_in_sync(&self) -> Option<u32> {
-        match self {
-            Synced::No { flashes: _ } => None,
-            Synced::Yes {
-                flashes: _,
-                synced_step,
-            } => Some(*synced_step),
-        }
-    }
-}
-
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 struct Octopus {
     energy: u32,
     flash: bool,
-    flashes: u32,
 }
 
 impl Octopus {
@@ -45,25 +23,24 @@ impl Octopus {
         Octopus {
             energy,
             flash: false,
-            flashes: 0,
         }
     }
 
-    fn step(&mut self) -> bool {
+    fn step(&mut self, gain: u32, threshold: u32) -> bool {
         if !self.flash {
-            self.energy += 1;
+            // A huge `--gain` keeps the octopus at the most energy it can hold
+            self.energy = self.energy.saturating_add(gain);
 
-            if self.energy > 9 {
+            if self.energy > threshold {
                 self.flash = true;
-                self.flashes += 1;
                 return true;
             }
         }
         false
     }
 
-    fn reset(&mut self) {
-        self.energy = 0;
+    fn reset(&mut self, energy: u32) {
+        self.energy = energy;
         self.flash = false;
     }
 }
@@ -92,12 +69,7 @@ impl Display for Octopus {
 
 trait OctopusGrid {
     fn print(&self);
-    fn step_neighbours(&mut self, x: usize, y: usize);
-    fn step(&mut self, x: usize, y: usize);
-    fn process_steps(&mut self, steps: u32) -> Synced;
-    fn in_sync(&self) -> bool;
-    fn total_flashes(&self) -> u32;
-    fn steps_to_get_in_sync(&mut self) -> u32;
+    fn step(&mut self, rules: &Rules) -> u32;
 }
 
 impl OctopusGrid for Grid<Octopus> {
@@ -110,159 +82,129 @@ impl OctopusGrid for Grid<Octopus> {
         }
     }
 
-    fn step_neighbours(&mut self, x: usize, y: usize) {
-        if let (Some(x), Some(y)) = (x.checked_add(1), y.checked_sub(1)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (x.checked_add(1), Some(y)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (x.checked_add(1), y.checked_add(1)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (Some(x), y.checked_sub(1)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (Some(x), y.checked_add(1)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (x.checked_sub(1), y.checked_sub(1)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (x.checked_sub(1), Some(y)) {
-            self.step(x, y);
-        }
-        if let (Some(x), Some(y)) = (x.checked_sub(1), y.checked_add(1)) {
-            self.step(x, y);
-        }
-    }
-
-    fn step(&mut self, x: usize, y: usize) {
-        if let Some(octopus) = self.get_mut(x, y) {
-            if octopus.step() {
-                self.step_neighbours(x, y);
-            }
-        }
-    }
-
-    fn process_steps(&mut self, steps: u32) -> Synced {
-        let mut synced_step: Option<u32> = None;
-
-        for i in 0..steps {
-            for x in 0..self.rows() {
-                for y in 0..self.cols() {
-                    self.step(x, y);
+    /// Raises the energy of every octopus by the gain and returns how many flashed. The flashing
+    /// octopuses wait in a queue to pass their energy on, so a cascade needs no recursion.
+    fn step(&mut self, rules: &Rules) -> u32 {
+        let (rows, cols) = (self.rows(), self.cols());
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        for x in 0..rows {
+            for y in 0..cols {
+                if self[x][y].step(rules.gain, rules.threshold) {
+                    queue.push_back((x, y));
                 }
             }
-            self.iter_mut().filter(|o| o.flash).for_each(|o| o.reset());
-
-            if self.in_sync() && synced_step.is_none() {
-                synced_step = Some(i + 1);
-            }
         }
 
-        let flashes = self.total_flashes();
-
-        if let Some(synced_step) = synced_step {
-            Synced::Yes {
-                flashes,
-                synced_step,
-            }
-        } else {
-            Synced::No { flashes }
-        }
-    }
-
-    fn in_sync(&self) -> bool {
-        self.iter()
-            .all(|x| x.energy == self.get(0, 0).unwrap().energy)
-    }
-
-    fn total_flashes(&self) -> u32 {
-        self.iter().map(|x| x.flashes).sum::<u32>()
-    }
-
-    fn steps_to_get_in_sync(&mut self) -> u32 {
-        let mut steps = 0;
-        while !self.in_sync() {
-            for x in 0..self.rows() {
-                for y in 0..self.cols() {
-                    self.step(x, y);
+        let mut flashes = queue.len() as u32;
+        while let Some((x, y)) = queue.pop_front() {
+            for (nx, ny) in rules.neighbours(x, y, rows, cols) {
+                if self[nx][ny].step(1, rules.threshold) {
+                    flashes += 1;
+                    queue.push_back((nx, ny));
                 }
             }
-            self.iter_mut().filter(|o| o.flash).for_each(|o| o.reset());
-            steps += 1;
-            if steps > 100_000 {
-                panic!("won't get in sync")
-            }
         }
-        steps
+        self.iter_mut()
+            .filter(|o| o.flash)
+            .for_each(|o| o.reset(rules.reset));
+        flashes
     }
 }
 
+/// Runs `cargo run -p dec-11 -- [--threshold N] [--gain N] [--reset N] [--von-neumann | --hex]
+/// [--wrap] [--flashes N]` to watch octopuses that follow other rules, or to find the step in
+/// which the flashes add up to `N`.
 fn main() {
+    let mut rules = Rules::default();
+    let mut flashes: Option<u64> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threshold" => rules.threshold = number_arg(args.next(), "--threshold"),
+            "--gain" => rules.gain = number_arg(args.next(), "--gain"),
+            "--reset" => rules.reset = number_arg(args.next(), "--reset"),
+            "--von-neumann" => rules.neighbourhood = Neighbourhood::VonNeumann,
+            "--hex" => rules.neighbourhood = Neighbourhood::Hex,
+            "--wrap" => rules.wrap = true,
+            "--flashes" => flashes = Some(number_arg(args.next(), "--flashes") as u64),
+            x => panic!("Unknown argument: {}", x),
+        }
+    }
+
     if let Ok(cavern) = read_input("./resources/input-dec-11") {
         println!("Solution 1:");
-        let mut cavern: Grid<Octopus> = cavern;
-        let result = cavern.process_steps(100);
-        println!("Total flashes: {}", result.flashes());
-        cavern.print();
+        let report = run(cavern, &rules, &[Stop::Steps(100)]);
+        println!("Total flashes: {}", report.flashes);
+        if let Some(most) = report.history.iter().max() {
+            println!("Most flashes in one step: {}", most);
+        }
+        report.cavern.print();
     }
 
     if let Ok(cavern) = read_input("./resources/input-dec-11") {
-        println!("\nSolution 2a:");
-        let mut cavern: Grid<Octopus> = cavern;
-        let result = cavern.process_steps(1000);
-        println!("Total flashes: {}", cavern.total_flashes());
-        if let Some(step) = result.step_in_sync() {
-            println!("Step {} all are pulsing at same time.", step);
+        println!("\nSolution 2:");
+        let report = run(cavern, &rules, &[Stop::Sync, Stop::Steps(100_000)]);
+        println!("Total flashes: {}", report.flashes);
+        match report.first_sync {
+            Some(step) => println!("Step {} all are pulsing at same time.", step),
+            None => println!("Not in sync after {} steps.", report.steps()),
         }
     }
 
-    if let Ok(cavern) = read_input("./resources/input-dec-11") {
-        println!("\nSolution 2b:");
-        let mut cavern: Grid<Octopus> = cavern;
-        let in_sync_step = cavern.steps_to_get_in_sync();
-        println!("Total flashes: {}", cavern.total_flashes());
-        println!("Step {} all are pulsing at same time.", in_sync_step);
+    if let (Some(target), Ok(cavern)) = (flashes, read_input("./resources/input-dec-11")) {
+        println!("\nFlash target:");
+        let report = run(
+            cavern,
+            &rules,
+            &[Stop::Flashes(target), Stop::Steps(100_000)],
+        );
+        match report.stopped_by {
+            Stop::Flashes(_) => {
+                println!("{} flashes after step {}.", report.flashes, report.steps())
+            }
+            _ => println!(
+                "Only {} flashes after {} steps.",
+                report.flashes,
+                report.steps()
+            ),
+        }
     }
 }
 
+fn number_arg(arg: Option<String>, name: &str) -> u32 {
+    arg.and_then(|n| n.parse::<u32>().ok())
+        .unwrap_or_else(|| panic!("{} needs a number", name))
+}
+
 fn read_input<P>(filename: P) -> io::Result<Grid<Octopus>>
 where
     P: AsRef<Path>,
 {
-    let mut map: Vec<Vec<Octopus>> = Vec::new();
-
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
-
-    reader.lines().for_each(|line| {
-        if let Ok(line) = line {
-            map.push(
-                line.trim()
-                    .chars()
-                    .map(|c| Octopus::new(c.to_digit(10).unwrap() as u32))
-                    .collect(),
-            )
-        }
-    });
-    let cols = map[0].len();
-    let map: Grid<Octopus> = Grid::from_vec(map.into_iter().flatten().into_iter().collect(), cols);
-
-    Ok(map)
+    let input = fs::read_to_string(filename)?;
+    let rows =
+        parse_digits(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let cols = rows[0].len();
+    let cells = rows.into_iter().flatten().map(Octopus::new).collect();
+    Ok(Grid::from_vec(cells, cols))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{read_input, OctopusGrid};
+    use crate::rules::{Neighbourhood, Rules};
+    use crate::run::{run, Stop};
+    use crate::{read_input, Octopus, OctopusGrid};
+    use aoc::differential::{inputs, Differential, Rectangle};
+    use aoc::y2021::octopuses;
+    use grid::Grid;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn solution_1() {
-        if let Ok(mut cavern) = read_input("../../resources/test-input-dec-11") {
-            let result = cavern.process_steps(100);
+        if let Ok(cavern) = read_input("../../resources/test-input-dec-11") {
+            let report = run(cavern, &Rules::default(), &[Stop::Steps(100)]);
 
-            assert_eq!(result.flashes(), 1656);
+            assert_eq!(report.flashes, 1656);
         } else {
             panic!("Fail!")
         }
@@ -270,9 +212,10 @@ mod tests {
 
     #[test]
     fn solution_2a() {
-        if let Ok(mut cavern) = read_input("../../resources/test-input-dec-11") {
-            let result = cavern.process_steps(1000);
-            assert_eq!(result.step_in_sync().unwrap(), 195);
+        if let Ok(cavern) = read_input("../../resources/test-input-dec-11") {
+            let report = run(cavern, &Rules::default(), &[Stop::Steps(1000)]);
+            assert_eq!(report.first_sync.unwrap(), 195);
+            assert_eq!(report.steps(), 1000);
         } else {
             panic!("Fail!")
         }
@@ -280,11 +223,202 @@ mod tests {
 
     #[test]
     fn solution_2b() {
-        if let Ok(mut cavern) = read_input("../../resources/test-input-dec-11") {
-            let result = cavern.steps_to_get_in_sync();
-            assert_eq!(result, 195);
+        if let Ok(cavern) = read_input("../../resources/test-input-dec-11") {
+            let report = run(cavern, &Rules::default(), &[Stop::Sync]);
+            assert_eq!(report.steps(), 195);
+            assert_eq!(report.stopped_by, Stop::Sync);
         } else {
             panic!("Fail!")
         }
     }
+
+    #[test]
+    fn history() {
+        let cavern = read_input("../../resources/test-input-dec-11").unwrap();
+        let report = run(cavern, &Rules::default(), &[Stop::Sync, Stop::Steps(100)]);
+        assert_eq!(report.stopped_by, Stop::Steps(100));
+        assert_eq!(report.first_sync, None);
+        assert_eq!(&report.history[..2], &[0, 35]);
+        assert_eq!(report.history.iter().sum::<u32>(), 1656);
+
+        let cavern = read_input("../../resources/test-input-dec-11").unwrap();
+        let report = run(cavern, &Rules::default(), &[Stop::Flashes(1656)]);
+        assert_eq!(report.steps(), 100);
+        assert_eq!(report.stopped_by, Stop::Flashes(1656));
+        // The example is all zeros again right after its first sync
+        let report = run(report.cavern, &Rules::default(), &[Stop::Sync]);
+        assert_eq!(report.steps(), 95);
+        assert!(report.cavern.iter().all(|octopus| octopus.energy == 0));
+    }
+
+    #[test]
+    fn large_cascade() {
+        // One octopus at full energy sets off all of the million others in a single step
+        let mut cavern: Grid<Octopus> = Grid::init(1000, 1000, Octopus::new(8));
+        cavern[500][500] = Octopus::new(9);
+        let report = run(cavern, &Rules::default(), &[Stop::Steps(2)]);
+        assert_eq!(report.history, vec![1_000_000, 0]);
+        assert_eq!(report.first_sync, Some(1));
+
+        let mut rng = StdRng::seed_from_u64(2021);
+        let cells = (0..1000 * 1000)
+            .map(|_| Octopus::new(rng.gen_range(0..10)))
+            .collect();
+        let cavern: Grid<Octopus> = Grid::from_vec(cells, 1000);
+        let report = run(cavern, &Rules::default(), &[Stop::Steps(3)]);
+        // Energy only goes up, so the octopuses at zero are the ones that just flashed
+        let flashed = report.cavern.iter().filter(|o| o.energy == 0).count();
+        assert_eq!(report.history[2] as usize, flashed);
+    }
+
+    fn cavern(rows: &[&str]) -> Grid<Octopus> {
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|c| Octopus::new(c.to_digit(10).unwrap())))
+            .collect();
+        Grid::from_vec(cells, rows[0].len())
+    }
+
+    fn energies(cavern: &Grid<Octopus>) -> Vec<u32> {
+        cavern.iter().map(|octopus| octopus.energy).collect()
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let rules = Rules::default();
+        let mut moore = cavern(&["000", "090", "000"]);
+        moore.step(&rules);
+        assert_eq!(energies(&moore), vec![2, 2, 2, 2, 0, 2, 2, 2, 2]);
+
+        let rules = Rules {
+            neighbourhood: Neighbourhood::VonNeumann,
+            ..Rules::default()
+        };
+        let mut von_neumann = cavern(&["000", "090", "000"]);
+        von_neumann.step(&rules);
+        assert_eq!(energies(&von_neumann), vec![1, 2, 1, 2, 0, 2, 1, 2, 1]);
+
+        let rules = Rules {
+            neighbourhood: Neighbourhood::Hex,
+            ..Rules::default()
+        };
+        let mut hex = cavern(&["000", "090", "000"]);
+        hex.step(&rules);
+        assert_eq!(energies(&hex), vec![1, 2, 2, 2, 0, 2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut flat = cavern(&["90000"]);
+        flat.step(&Rules::default());
+        assert_eq!(energies(&flat), vec![0, 2, 1, 1, 1]);
+
+        let rules = Rules {
+            wrap: true,
+            ..Rules::default()
+        };
+        let mut ring = cavern(&["90000"]);
+        ring.step(&rules);
+        assert_eq!(energies(&ring), vec![0, 2, 1, 1, 2]);
+        // Two columns wrap onto the same neighbour, it only gets the energy once
+        let mut pair = cavern(&["90"]);
+        pair.step(&rules);
+        assert_eq!(energies(&pair), vec![0, 2]);
+    }
+
+    #[test]
+    fn threshold_gain_and_reset() {
+        let rules = Rules {
+            threshold: 5,
+            gain: 3,
+            reset: 1,
+            ..Rules::default()
+        };
+        let report = run(cavern(&["000", "000"]), &rules, &[Stop::Steps(4)]);
+        assert_eq!(report.history, vec![0, 6, 0, 6]);
+        assert_eq!(report.first_sync, Some(2));
+        assert_eq!(energies(&report.cavern), vec![1; 6]);
+
+        let full = Rules {
+            threshold: u32::MAX,
+            gain: u32::MAX,
+            ..Rules::default()
+        };
+        let report = run(cavern(&["090", "000"]), &full, &[Stop::Steps(2)]);
+        assert_eq!(report.history, vec![0, 0]);
+        assert_eq!(energies(&report.cavern), vec![u32::MAX; 6]);
+    }
+
+    /// A step that scans the whole cavern for new flashes until there are none.
+    fn scanning_step(energy: &mut [Vec<u32>], rules: &Rules) -> u32 {
+        let (rows, cols) = (energy.len(), energy[0].len());
+        let mut flashed = vec![vec![false; cols]; rows];
+        energy
+            .iter_mut()
+            .flatten()
+            .for_each(|e| *e = e.saturating_add(rules.gain));
+        let mut flashes = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for x in 0..rows {
+                for y in 0..cols {
+                    if flashed[x][y] || energy[x][y] <= rules.threshold {
+                        continue;
+                    }
+                    flashed[x][y] = true;
+                    flashes += 1;
+                    changed = true;
+                    for (nx, ny) in rules.neighbours(x, y, rows, cols) {
+                        energy[nx][ny] += 1;
+                    }
+                }
+            }
+        }
+        for (x, y) in (0..rows).flat_map(|x| (0..cols).map(move |y| (x, y))) {
+            if flashed[x][y] {
+                energy[x][y] = rules.reset;
+            }
+        }
+        flashes
+    }
+
+    #[test]
+    fn differential() {
+        let variants = [
+            Rules::default(),
+            Rules {
+                neighbourhood: Neighbourhood::Hex,
+                wrap: true,
+                ..Rules::default()
+            },
+            Rules {
+                threshold: 5,
+                gain: 2,
+                reset: 1,
+                neighbourhood: Neighbourhood::VonNeumann,
+                wrap: false,
+            },
+        ];
+        for rules in variants {
+            Differential::new("scan", |(cells, steps): &(Rectangle<u32>, u8)| {
+                let mut energy = cells.0.clone();
+                (0..*steps)
+                    .map(|_| scanning_step(&mut energy, &rules))
+                    .collect::<Vec<u32>>()
+            })
+            .register("queue", |(cells, steps)| {
+                let octopuses = cells.0.iter().flatten().map(|&e| Octopus::new(e));
+                let cavern = Grid::from_vec(octopuses.collect(), cells.0[0].len());
+                run(cavern, &rules, &[Stop::Steps(*steps as usize)]).history
+            })
+            .assert(inputs(30, 11, |rng| {
+                let cavern = octopuses(rng, 6, 7).unwrap();
+                let rows = cavern
+                    .lines()
+                    .map(|row| row.chars().map(|c| c.to_digit(10).unwrap()).collect());
+                (Rectangle(rows.collect()), rng.gen_range(1..=40))
+            }));
+        }
+    }
 }
@@ -0,0 +1,89 @@
+/// Which octopuses a flash reaches.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Neighbourhood {
+    /// The four octopuses sharing an edge.
+    VonNeumann,
+    /// The eight octopuses sharing an edge or a corner.
+    #[default]
+    Moore,
+    /// Six octopuses, every row shifted half an octopus to the right of the one above it.
+    Hex,
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Neighbourhood::Hex => &[(-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0)],
+        }
+    }
+}
+
+/// How the octopuses gain energy and flash.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rules {
+    /// An octopus flashes once its energy is above the threshold.
+    pub(crate) threshold: u32,
+    /// The energy every octopus gains at the start of a step.
+    pub(crate) gain: u32,
+    /// The energy of an octopus after it flashed.
+    pub(crate) reset: u32,
+    pub(crate) neighbourhood: Neighbourhood,
+    /// The edges of the cavern wrap around to the other side.
+    pub(crate) wrap: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            threshold: 9,
+            gain: 1,
+            reset: 0,
+            neighbourhood: Neighbourhood::Moore,
+            wrap: false,
+        }
+    }
+}
+
+impl Rules {
+    /// The octopuses a flash at (`x`, `y`) reaches in a cavern of `rows` by `cols`. On a small
+    /// wrapping cavern two offsets can end up at the same octopus, it is reached only once.
+    pub(crate) fn neighbours(
+        &self,
+        x: usize,
+        y: usize,
+        rows: usize,
+        cols: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut neighbours: Vec<(usize, usize)> = self
+            .neighbourhood
+            .offsets()
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                if self.wrap {
+                    let x = (x as isize + dx).rem_euclid(rows as isize) as usize;
+                    let y = (y as isize + dy).rem_euclid(cols as isize) as usize;
+                    Some((x, y))
+                } else {
+                    Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+                }
+            })
+            .filter(|&(nx, ny)| nx < rows && ny < cols && (nx, ny) != (x, y))
+            .collect();
+        if self.wrap {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+        neighbours
+    }
+}
@@ -0,0 +1,67 @@
+use crate::rules::Rules;
+use crate::{Octopus, OctopusGrid};
+use grid::Grid;
+
+/// When a run ends, the first condition met wins.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Stop {
+    /// After this many steps.
+    Steps(usize),
+    /// After the first step in which every octopus flashed.
+    Sync,
+    /// Once this many flashes happened in total.
+    Flashes(u64),
+}
+
+impl Stop {
+    fn reached(&self, report: &Report) -> bool {
+        match *self {
+            Stop::Steps(steps) => report.history.len() >= steps,
+            Stop::Sync => report.first_sync.is_some(),
+            Stop::Flashes(target) => report.flashes >= target,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Report {
+    pub(crate) flashes: u64,
+    /// The number of flashes of every step, in order.
+    pub(crate) history: Vec<u32>,
+    /// The first step, counting from 1, in which every octopus flashed.
+    pub(crate) first_sync: Option<usize>,
+    pub(crate) stopped_by: Stop,
+    pub(crate) cavern: Grid<Octopus>,
+}
+
+impl Report {
+    pub(crate) fn steps(&self) -> usize {
+        self.history.len()
+    }
+}
+
+/// Steps the octopuses until one of `stops` is met. Without a `Stop::Steps` among them a run
+/// that never meets the others doesn't end.
+pub(crate) fn run(cavern: Grid<Octopus>, rules: &Rules, stops: &[Stop]) -> Report {
+    let octopuses = cavern.rows() * cavern.cols();
+    let mut report = Report {
+        flashes: 0,
+        history: Vec::new(),
+        first_sync: None,
+        stopped_by: Stop::Steps(0),
+        cavern,
+    };
+    loop {
+        if let Some(&stop) = stops.iter().find(|stop| stop.reached(&report)) {
+            report.stopped_by = stop;
+            return report;
+        }
+        let flashes = report.cavern.step(rules);
+        report.flashes += flashes as u64;
+        report.history.push(flashes);
+        // Every octopus flashes at most once in a step, so they all did
+        if flashes as usize == octopuses && report.first_sync.is_none() {
+            report.first_sync = Some(report.history.len());
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! The planned course of the submarine, one command per line.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    Up(i32),
+    Down(i32),
+    Forward(i32),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    MissingAmount { line: usize },
+    UnknownDirection { line: usize, direction: String },
+    InvalidAmount { line: usize, value: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingAmount { line } => {
+                write!(f, "Line {}: expected a direction and an amount", line)
+            }
+            ParseError::UnknownDirection { line, direction } => {
+                write!(f, "Line {}: {:?} is not a direction", line, direction)
+            }
+            ParseError::InvalidAmount { line, value } => {
+                write!(f, "Line {}: {:?} is not an amount", line, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_command(line: usize, text: &str) -> Result<Direction, ParseError> {
+    let Some((direction, amount)) = text.split_once(char::is_whitespace) else {
+        return Err(ParseError::MissingAmount { line });
+    };
+    let amount = amount.trim();
+    let amount = amount
+        .parse::<i32>()
+        .map_err(|_| ParseError::InvalidAmount {
+            line,
+            value: amount.to_string(),
+        })?;
+    match direction {
+        "up" => Ok(Direction::Up(amount)),
+        "down" => Ok(Direction::Down(amount)),
+        "forward" => Ok(Direction::Forward(amount)),
+        _ => Err(ParseError::UnknownDirection {
+            line,
+            direction: direction.to_string(),
+        }),
+    }
+}
+
+/// One `direction amount` per line, blank lines are skipped.
+pub fn parse_input(input: &str) -> Result<Vec<Direction>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line, text)| parse_command(line, text))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, Direction, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("forward 5\r\n\ndown  2\nup 3\n"),
+            Ok(vec![
+                Direction::Forward(5),
+                Direction::Down(2),
+                Direction::Up(3)
+            ])
+        );
+        assert_eq!(
+            parse_input("forward 5\nbackward 2"),
+            Err(ParseError::UnknownDirection {
+                line: 2,
+                direction: "backward".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("up x"),
+            Err(ParseError::InvalidAmount {
+                line: 1,
+                value: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("down"),
+            Err(ParseError::MissingAmount { line: 1 })
+        );
+    }
+}
@@ -1,26 +1,8 @@
-use std::fs::File;
+use dec_2::{parse_input, Direction};
+use std::fs;
 use std::io;
-use std::io::BufRead;
 use std::path::Path;
 
-#[derive(Debug)]
-enum Direction {
-    Up(i32),
-    Down(i32),
-    Forward(i32),
-}
-
-impl Direction {
-    fn create(direction: &str, amount: i32) -> Direction {
-        match direction {
-            "up" => Direction::Up(amount),
-            "down" => Direction::Down(amount),
-            "forward" => Direction::Forward(amount),
-            _ => panic!("Could not match: {}", direction),
-        }
-    }
-}
-
 #[derive(Debug)]
 struct Location {
     depth: i32,
@@ -83,16 +65,8 @@ fn read_input<P>(filename: P) -> io::Result<Vec<Direction>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
-    let mut x: Vec<Direction> = Vec::new();
-    io::BufReader::new(file).lines().for_each(|l| {
-        let string = l.unwrap();
-        let line = string.split_once(" ");
-        if let Some((direction, amount)) = line {
-            x.push(Direction::create(direction, amount.parse::<i32>().unwrap()));
-        }
-    });
-    Ok(x)
+    let input = fs::read_to_string(filename)?;
+    parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
 }
 
 #[cfg(test)]
@@ -0,0 +1,125 @@
+//! The binary numbers of the diagnostic report.
+
+use std::fmt::{Display, Formatter};
+
+/// Two rates are multiplied as a `u64`, so each of them fits in half of that.
+pub const MAX_WIDTH: usize = 32;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    Empty,
+    InvalidBit {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    TooWide {
+        width: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "No binary numbers found"),
+            ParseError::InvalidBit {
+                line,
+                column,
+                found,
+            } => write!(f, "Line {}:{}: {:?} is not a bit", line, column, found),
+            ParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: expected {} bits like the numbers above, found {}",
+                line, expected, found
+            ),
+            ParseError::TooWide { width } => write!(
+                f,
+                "Numbers of {} bits are wider than {} bits",
+                width, MAX_WIDTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// One binary number per line as its ASCII digits, all of the same width. Blank lines are
+/// skipped.
+pub fn parse_input(input: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    let mut numbers: Vec<Vec<u8>> = Vec::new();
+    for (line, text) in input.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if text.is_empty() {
+            continue;
+        }
+        if let Some((column, found)) = text
+            .chars()
+            .enumerate()
+            .find(|&(_, c)| c != '0' && c != '1')
+        {
+            return Err(ParseError::InvalidBit {
+                line,
+                column: column + 1,
+                found,
+            });
+        }
+        if let Some(first) = numbers.first() {
+            if first.len() != text.len() {
+                return Err(ParseError::RaggedRow {
+                    line,
+                    expected: first.len(),
+                    found: text.len(),
+                });
+            }
+        } else if text.len() > MAX_WIDTH {
+            return Err(ParseError::TooWide { width: text.len() });
+        }
+        numbers.push(text.as_bytes().to_vec());
+    }
+    if numbers.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Ok(numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("00100\r\n11110\n\n"),
+            Ok(vec![b"00100".to_vec(), b"11110".to_vec()])
+        );
+        assert_eq!(
+            parse_input("00100\n11210"),
+            Err(ParseError::InvalidBit {
+                line: 2,
+                column: 3,
+                found: '2'
+            })
+        );
+        assert_eq!(
+            parse_input("00100\n1111"),
+            Err(ParseError::RaggedRow {
+                line: 2,
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(
+            parse_input(&"1".repeat(33)),
+            Err(ParseError::TooWide { width: 33 })
+        );
+        assert_eq!(parse_input("\n \n"), Err(ParseError::Empty));
+    }
+}
@@ -1,6 +1,6 @@
-use std::fs::File;
+use dec_3::parse_input;
+use std::fs;
 use std::io;
-use std::io::BufRead;
 use std::path::Path;
 
 fn main() {
@@ -9,11 +9,11 @@ fn main() {
     println!("support: {}", calculate_life_support(&input));
 }
 
-fn bin_vec_to_int(bin: &[u8]) -> i32 {
-    i32::from_str_radix(&String::from_utf8(bin.to_owned()).unwrap(), 2).unwrap()
+fn bin_vec_to_int(bin: &[u8]) -> u64 {
+    u64::from_str_radix(std::str::from_utf8(bin).unwrap(), 2).unwrap()
 }
 
-fn calculate_power(input: &[Vec<u8>]) -> i32 {
+fn calculate_power(input: &[Vec<u8>]) -> u64 {
     let mut gamma: Vec<u8> = vec![];
     let mut epsilon: Vec<u8> = vec![];
     for i in 0..input[0].len() {
@@ -34,7 +34,7 @@ fn calculate_power(input: &[Vec<u8>]) -> i32 {
     gamma * epsilon
 }
 
-fn calculate_life_support(input: &[Vec<u8>]) -> i32 {
+fn calculate_life_support(input: &[Vec<u8>]) -> u64 {
     let mut oxygen = input.to_owned();
     for c in 0..oxygen[0].len() {
         let mut ones = 0;
@@ -67,6 +67,10 @@ fn calculate_life_support(input: &[Vec<u8>]) -> i32 {
                 zeros += 1;
             }
         }
+        if zeros == 0 || ones == 0 {
+            // All numbers left share this bit, neither value is less common
+            continue;
+        }
         if zeros <= ones {
             co2.retain(|x| x[c] == b'0');
         } else {
@@ -85,19 +89,15 @@ fn read_input<P>(filename: P) -> io::Result<Vec<Vec<u8>>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
-    let mut x: Vec<Vec<u8>> = Vec::new();
-    io::BufReader::new(file).lines().for_each(|line| {
-        if let Ok(line) = line {
-            x.push(line.into_bytes());
-        }
-    });
-    Ok(x)
+    let input = fs::read_to_string(filename)?;
+    parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{calculate_life_support, calculate_power};
+    use aoc::{generate, Params};
+    use dec_3::parse_input;
 
     #[test]
     fn test() {
@@ -118,4 +118,30 @@ mod tests {
         assert_eq!(calculate_power(&input), 198);
         assert_eq!(calculate_life_support(&input), 230);
     }
+
+    #[test]
+    fn co2_shares_a_bit() {
+        // After the first bit only 010 and 011 are left, both with a 1 in the middle
+        let input: Vec<Vec<u8>> = ["100", "101", "010", "011", "111"]
+            .iter()
+            .map(|number| number.as_bytes().to_vec())
+            .collect();
+        assert_eq!(calculate_life_support(&input), 0b101 * 0b010);
+    }
+
+    #[test]
+    fn generated_wide_numbers() {
+        for width in [20, 31, 32] {
+            let params = Params {
+                seed: width as u64,
+                count: Some(1000),
+                width: Some(width),
+                ..Params::default()
+            };
+            let input = parse_input(&generate(2021, 3, &params).unwrap()).unwrap();
+            let widest = (1 << width) - 1;
+            assert!(calculate_power(&input) <= widest * widest);
+            assert!(calculate_life_support(&input) <= widest * widest);
+        }
+    }
 }
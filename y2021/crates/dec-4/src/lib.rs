@@ -0,0 +1,123 @@
+//! The drawn numbers and the bingo cards.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The rows of numbers on one card.
+pub type Rows = Vec<Vec<i32>>;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    MissingDraws,
+    InvalidNumber {
+        line: usize,
+        value: String,
+    },
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingDraws => write!(f, "No line with drawn numbers found"),
+            ParseError::InvalidNumber { line, value } => {
+                write!(f, "Line {}: {:?} is not a number", line, value)
+            }
+            ParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: expected {} numbers like the rows above, found {}",
+                line, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_number<T: FromStr>(value: &str, line: usize) -> Result<T, ParseError> {
+    value.parse::<T>().map_err(|_| ParseError::InvalidNumber {
+        line,
+        value: value.to_string(),
+    })
+}
+
+/// Parses the drawn numbers followed by blank line separated cards. Line endings, trailing
+/// whitespace and the final blank line are not significant; every card has to be rectangular.
+pub fn parse_input(input: &str) -> Result<(Vec<i32>, Vec<Rows>), ParseError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .skip_while(|(_, line)| line.is_empty());
+
+    let numbers = match lines.next() {
+        Some((line, draws)) => draws
+            .split(',')
+            .map(|x| parse_number::<i32>(x.trim(), line))
+            .collect::<Result<Vec<i32>, ParseError>>()?,
+        None => return Err(ParseError::MissingDraws),
+    };
+
+    let mut cards: Vec<Rows> = Vec::new();
+    let mut rows: Rows = Vec::new();
+    for (line, text) in lines {
+        if text.is_empty() {
+            if !rows.is_empty() {
+                cards.push(std::mem::take(&mut rows));
+            }
+            continue;
+        }
+
+        let row = text
+            .split_whitespace()
+            .map(|x| parse_number::<i32>(x, line))
+            .collect::<Result<Vec<i32>, ParseError>>()?;
+        if let Some(first) = rows.first() {
+            if first.len() != row.len() {
+                return Err(ParseError::RaggedRow {
+                    line,
+                    expected: first.len(),
+                    found: row.len(),
+                });
+            }
+        }
+        rows.push(row);
+    }
+    if !rows.is_empty() {
+        cards.push(rows);
+    }
+    Ok((numbers, cards))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("1,2\n\n1 2\n3 4 5\n").err(),
+            Some(ParseError::RaggedRow {
+                line: 4,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            parse_input("1,x").err(),
+            Some(ParseError::InvalidNumber {
+                line: 1,
+                value: "x".to_string()
+            })
+        );
+        assert_eq!(parse_input("\n\n").err(), Some(ParseError::MissingDraws));
+    }
+}
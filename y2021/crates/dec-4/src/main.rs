@@ -1,20 +1,11 @@
 use anstyle::Style;
+use dec_4::{parse_input, ParseError};
 use grid::*;
-use io::BufReader;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::BufRead;
-use std::iter::StepBy;
 use std::path::Path;
-use std::slice::Iter;
-use std::str::FromStr;
-
-#[derive(Eq, PartialEq)]
-enum Puzzle {
-    One,
-    Two,
-}
 
 #[derive(Default, Clone, Copy, Debug)]
 struct Number {
@@ -22,17 +13,6 @@ struct Number {
     marked: bool,
 }
 
-impl FromStr for Number {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self {
-            value: s.parse::<i32>().unwrap(),
-            marked: false,
-        })
-    }
-}
-
 impl Display for Number {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let number = match self.marked {
@@ -60,17 +40,15 @@ struct Card {
 }
 
 impl Card {
-    fn new(rows: usize, cols: usize) -> Card {
+    /// Builds a card from rows that all have the same, non-zero, length.
+    fn from_rows(rows: Vec<Vec<Number>>) -> Card {
+        let cols = rows[0].len();
         Card {
-            field: Grid::new(rows, cols),
+            field: Grid::from_vec(rows.into_iter().flatten().collect(), cols),
             bingo: false,
         }
     }
 
-    fn push_row(&mut self, row: Vec<Number>) {
-        self.field.push_row(row)
-    }
-
     fn rows(&self) -> usize {
         self.field.rows()
     }
@@ -79,20 +57,55 @@ impl Card {
         self.field.cols()
     }
 
-    fn iter_col(&self, i: usize) -> StepBy<Iter<Number>> {
-        self.field.iter_col(i)
-    }
-
-    fn iter_row(&self, i: usize) -> Iter<Number> {
-        self.field.iter_row(i)
-    }
-
-    fn clear(&mut self) {
-        self.field.clear();
-    }
+    /// All lines of this card that count as a bingo under `rules`, as lists of cell indices.
+    fn lines(&self, rules: &Rules) -> Vec<(Line, Vec<usize>)> {
+        let (rows, cols) = (self.rows(), self.cols());
+        let cell = |row: usize, col: usize| row * cols + col;
+        let mut lines: Vec<(Line, Vec<usize>)> = Vec::new();
 
-    fn is_empty(&self) -> bool {
-        self.field.is_empty()
+        for row in 0..rows {
+            lines.push((
+                Line::Row(row),
+                (0..cols).map(|col| cell(row, col)).collect(),
+            ));
+        }
+        for col in 0..cols {
+            lines.push((
+                Line::Column(col),
+                (0..rows).map(|row| cell(row, col)).collect(),
+            ));
+        }
+        if rules.diagonals && rows == cols {
+            lines.push((Line::Diagonal, (0..rows).map(|i| cell(i, i)).collect()));
+            lines.push((
+                Line::AntiDiagonal,
+                (0..rows).map(|i| cell(i, cols - 1 - i)).collect(),
+            ));
+        }
+        if rules.corners && rows > 0 && cols > 0 {
+            let mut corners = vec![
+                cell(0, 0),
+                cell(0, cols - 1),
+                cell(rows - 1, 0),
+                cell(rows - 1, cols - 1),
+            ];
+            corners.sort_unstable();
+            corners.dedup();
+            lines.push((Line::Corners, corners));
+        }
+        if rules.full_card {
+            lines.push((Line::FullCard, (0..rows * cols).collect()));
+        }
+        for (i, pattern) in rules.custom.iter().enumerate() {
+            if pattern.iter().all(|&(row, col)| row < rows && col < cols) {
+                let mut cells: Vec<usize> =
+                    pattern.iter().map(|&(row, col)| cell(row, col)).collect();
+                cells.sort_unstable();
+                cells.dedup();
+                lines.push((Line::Custom(i), cells));
+            }
+        }
+        lines
     }
 
     fn had_bingo(&self) -> bool {
@@ -134,111 +147,315 @@ impl Card {
     }
 }
 
-fn main() {
-    if let Ok((numbers, cards)) = read_input("./resources/input-dec-4") {
-        let bingo = play_bingo(numbers, cards, Puzzle::One);
-        println!("Bingo: {}", bingo);
+/// A set of cells that completes a bingo once every one of them is marked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Line {
+    Row(usize),
+    Column(usize),
+    Diagonal,
+    AntiDiagonal,
+    Corners,
+    FullCard,
+    Custom(usize),
+}
+
+/// Which patterns count as a bingo. Rows and columns always do; everything else is opt-in.
+#[derive(Clone, Debug, Default)]
+struct Rules {
+    diagonals: bool,
+    corners: bool,
+    full_card: bool,
+    custom: Vec<Vec<(usize, usize)>>,
+}
+
+impl Rules {
+    fn with_diagonals(mut self) -> Rules {
+        self.diagonals = true;
+        self
     }
 
-    if let Ok((numbers, cards)) = read_input("./resources/input-dec-4") {
-        let bingo = play_bingo(numbers, cards, Puzzle::Two);
-        println!("Last Bingo: {}", bingo);
+    fn with_corners(mut self) -> Rules {
+        self.corners = true;
+        self
+    }
+
+    fn with_full_card(mut self) -> Rules {
+        self.full_card = true;
+        self
+    }
+
+    /// Adds a pattern of `(row, col)` cells. Cards the pattern does not fit on ignore it.
+    fn with_pattern(mut self, cells: Vec<(usize, usize)>) -> Rules {
+        self.custom.push(cells);
+        self
     }
 }
 
-fn play_bingo(numbers: Vec<i32>, mut cards: Vec<Card>, puzzle: Puzzle) -> i32 {
-    let mut last_card = false;
-    for number in numbers.iter() {
-        cards.retain(|card| !card.had_bingo());
-        if cards.len() == 1 {
-            last_card = true;
+/// Per card bookkeeping: how many cells of every line are marked and which lines a cell is in.
+struct Tracker {
+    lines: Vec<Line>,
+    hits: Vec<usize>,
+    sizes: Vec<usize>,
+    cell_lines: Vec<Vec<usize>>,
+}
+
+impl Tracker {
+    fn new(card: &Card, rules: &Rules) -> Tracker {
+        let mut tracker = Tracker {
+            lines: Vec::new(),
+            hits: Vec::new(),
+            sizes: Vec::new(),
+            cell_lines: vec![Vec::new(); card.rows() * card.cols()],
+        };
+        for (line, cells) in card.lines(rules) {
+            let id = tracker.lines.len();
+            tracker.lines.push(line);
+            tracker.hits.push(0);
+            tracker.sizes.push(cells.len());
+            for cell in cells {
+                tracker.cell_lines[cell].push(id);
+            }
         }
+        tracker
+    }
 
-        let mut check_card = false;
-        for card in cards.iter_mut() {
-            for card_number in card.field.iter_mut() {
-                if card_number.value.eq(number) {
-                    card_number.marked = true;
-                    check_card = true;
-                }
+    /// Registers a marked cell and returns the first line it completed, if any.
+    fn mark(&mut self, cell: usize) -> Option<Line> {
+        let mut completed = None;
+        for &id in self.cell_lines[cell].iter() {
+            self.hits[id] += 1;
+            if self.hits[id] == self.sizes[id] && completed.is_none() {
+                completed = Some(self.lines[id]);
+            }
+        }
+        completed
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Win {
+    card: usize,
+    draw: usize,
+    line: Line,
+    number: i32,
+    score: i32,
+}
+
+/// Bingo hall for cards of any size. Drawn numbers are looked up in an index of
+/// `number -> (card, cell)` so calling a number only touches the cells that hold it.
+struct Bingo {
+    cards: Vec<Card>,
+    trackers: Vec<Tracker>,
+    index: HashMap<i32, Vec<(usize, usize)>>,
+    draws: usize,
+}
+
+impl Bingo {
+    fn new(cards: Vec<Card>, rules: &Rules) -> Bingo {
+        let mut index: HashMap<i32, Vec<(usize, usize)>> = HashMap::new();
+        for (c, card) in cards.iter().enumerate() {
+            for (cell, number) in card.field.iter().enumerate() {
+                index.entry(number.value).or_default().push((c, cell));
             }
-            if check_card {
-                check_card = false;
-                for row in 0..card.rows() {
-                    if card.iter_row(row).filter(|x| x.marked.eq(&true)).count() == 5 {
-                        card.bingo();
-                        if last_card || puzzle == Puzzle::One {
-                            return card.calculate_print_winner(number);
-                        }
+        }
+        let trackers = cards.iter().map(|card| Tracker::new(card, rules)).collect();
+        Bingo {
+            cards,
+            trackers,
+            index,
+            draws: 0,
+        }
+    }
+
+    /// Marks `number` on every card that has not won yet and returns the cards that won by it.
+    fn call(&mut self, number: i32) -> Vec<Win> {
+        let draw = self.draws;
+        self.draws += 1;
+
+        let mut won: Vec<(usize, Line)> = Vec::new();
+        if let Some(positions) = self.index.get(&number) {
+            for &(c, cell) in positions {
+                let card = &mut self.cards[c];
+                if card.had_bingo() {
+                    continue;
+                }
+                let cols = card.cols();
+                if let Some(n) = card.field.get_mut(cell / cols, cell % cols) {
+                    if n.marked {
+                        continue;
                     }
+                    n.marked = true;
                 }
-                for col in 0..card.cols() {
-                    if card.iter_col(col).filter(|x| x.marked.eq(&true)).count() == 5 {
-                        card.bingo();
-                        if last_card || puzzle == Puzzle::One {
-                            return card.calculate_print_winner(number);
-                        }
+                if let Some(line) = self.trackers[c].mark(cell) {
+                    if !won.iter().any(|(w, _)| *w == c) {
+                        won.push((c, line));
                     }
                 }
             }
         }
+
+        won.into_iter()
+            .map(|(c, line)| {
+                let card = &mut self.cards[c];
+                card.bingo();
+                Win {
+                    card: c,
+                    draw,
+                    line,
+                    number,
+                    score: number * card.sum_unmarked(),
+                }
+            })
+            .collect()
     }
-    panic!("No winner found!")
 }
 
-fn read_input<P>(filename: P) -> io::Result<(Vec<i32>, Vec<Card>)>
-where
-    P: AsRef<Path>,
-{
-    let mut numbers: Vec<i32> = Vec::new();
-    let mut cards: Vec<Card> = Vec::new();
-
-    let file = File::open(filename)?;
-    let mut reader = BufReader::new(file);
-
-    let mut numbers_input: String = String::new();
-    if reader.read_line(&mut numbers_input).is_ok() {
-        numbers = numbers_input
-            .trim()
-            .split(',')
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect();
+/// Builds the rules from the command line, e.g. `--diagonals --pattern=0,0/2,2/4,4`.
+fn rules_from_args() -> Rules {
+    let mut rules = Rules::default();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--diagonals" => rules = rules.with_diagonals(),
+            "--corners" => rules = rules.with_corners(),
+            "--full-card" => rules = rules.with_full_card(),
+            x if x.starts_with("--pattern=") => {
+                let cells = parse_pattern(&x["--pattern=".len()..]);
+                rules = rules.with_pattern(cells.unwrap_or_else(|error| panic!("{}", error)));
+            }
+            x => panic!("Unknown argument: {}", x),
+        }
+    }
+    rules
+}
+
+/// The `row,col` cells of a pattern, separated by `/`.
+fn parse_pattern(text: &str) -> Result<Vec<(usize, usize)>, String> {
+    text.split('/')
+        .map(|cell| {
+            cell.split_once(',')
+                .and_then(|(row, col)| Some((row.parse().ok()?, col.parse().ok()?)))
+                .ok_or_else(|| format!("Invalid cell in pattern: {:?}", cell))
+        })
+        .collect()
+}
+
+/// The outcome of playing every draw: the wins in the order they happened and the cards
+/// that never got a bingo.
+struct Timeline {
+    cards: Vec<Card>,
+    wins: Vec<Win>,
+    losers: Vec<usize>,
+}
+
+impl Timeline {
+    fn first(&self) -> Option<&Win> {
+        self.wins.first()
     }
 
-    let mut card: Card = Card::new(5, 5);
-    card.clear();
-    reader.lines().for_each(|line| {
-        if let Ok(line) = line {
-            match line.trim() {
-                x if x.is_empty() => {
-                    if !card.is_empty() {
-                        cards.push(card.clone());
-                    }
-                    card.clear();
-                }
-                x => {
-                    let row: Vec<Number> = x
-                        .split_whitespace()
-                        .map(|x| x.parse::<Number>().unwrap())
-                        .collect();
-                    card.push_row(row);
-                }
+    fn last(&self) -> Option<&Win> {
+        self.wins.last()
+    }
+
+    fn print(&self) {
+        for win in self.wins.iter() {
+            println!(
+                "Card {} won at draw {} ({}) with {:?}, score: {}",
+                win.card, win.draw, win.number, win.line, win.score
+            );
+        }
+        for card in self.losers.iter() {
+            println!("Card {} never won", card);
+        }
+    }
+}
+
+/// Plays all `numbers` on `cards` and records when each card won.
+fn simulate(numbers: Vec<i32>, cards: Vec<Card>, rules: &Rules) -> Timeline {
+    let mut bingo = Bingo::new(cards, rules);
+    let mut wins: Vec<Win> = Vec::new();
+    for number in numbers {
+        if wins.len() == bingo.cards.len() {
+            break;
+        }
+        wins.append(&mut bingo.call(number));
+    }
+    let losers = (0..bingo.cards.len())
+        .filter(|&c| !bingo.cards[c].had_bingo())
+        .collect();
+    Timeline {
+        cards: bingo.cards,
+        wins,
+        losers,
+    }
+}
+
+fn main() {
+    let rules = rules_from_args();
+
+    if let Ok((numbers, cards)) = read_input("./resources/input-dec-4") {
+        let timeline = simulate(numbers, cards, &rules);
+        timeline.print();
+
+        match timeline.first() {
+            Some(win) => {
+                let bingo = timeline.cards[win.card].calculate_print_winner(&win.number);
+                println!("Bingo: {}", bingo);
             }
+            None => println!("No winner found!"),
+        }
+        if let Some(win) = timeline.last() {
+            let bingo = timeline.cards[win.card].calculate_print_winner(&win.number);
+            println!("Last Bingo: {}", bingo);
         }
-    });
+    }
+}
+
+/// The drawn numbers and the cards, see `dec_4::parse_input` for the format.
+fn parse_cards(input: &str) -> Result<(Vec<i32>, Vec<Card>), ParseError> {
+    let (numbers, cards) = parse_input(input)?;
+    let cards = cards
+        .into_iter()
+        .map(|rows| {
+            Card::from_rows(
+                rows.into_iter()
+                    .map(|row| {
+                        row.into_iter()
+                            .map(|value| Number {
+                                value,
+                                marked: false,
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
     Ok((numbers, cards))
 }
 
+fn read_input<P>(filename: P) -> io::Result<(Vec<i32>, Vec<Card>)>
+where
+    P: AsRef<Path>,
+{
+    let input = fs::read_to_string(filename)?;
+    parse_cards(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{play_bingo, read_input, Puzzle};
+    use crate::{
+        parse_cards, parse_pattern, read_input, simulate, Bingo, Card, Line, Number, Rules,
+    };
 
     #[test]
     fn test_1() {
         let expected = 4512;
         match read_input("../../resources/test-input-dec-4") {
             Ok((numbers, cards)) => {
-                let bingo = play_bingo(numbers, cards, Puzzle::One);
+                let bingo = simulate(numbers, cards, &Rules::default())
+                    .first()
+                    .unwrap()
+                    .score;
                 assert_eq!(
                     bingo, expected,
                     "Response: {}, should be: {}",
@@ -257,7 +474,10 @@ mod tests {
         let expected = 1924;
         match read_input("../../resources/test-input-dec-4") {
             Ok((numbers, cards)) => {
-                let bingo = play_bingo(numbers, cards, Puzzle::Two);
+                let bingo = simulate(numbers, cards, &Rules::default())
+                    .last()
+                    .unwrap()
+                    .score;
                 assert_eq!(
                     bingo, expected,
                     "Response: {}, should be: {}",
@@ -270,4 +490,93 @@ mod tests {
             }
         }
     }
+
+    fn card(rows: &[&[i32]]) -> Card {
+        Card::from_rows(
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&value| Number {
+                            value,
+                            marked: false,
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn non_square_card() {
+        let cards = vec![card(&[&[1, 2, 3], &[4, 5, 6]])];
+        let mut bingo = Bingo::new(cards, &Rules::default());
+        assert!(bingo.call(1).is_empty());
+        let wins = bingo.call(4);
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].line, Line::Column(0));
+        assert_eq!(wins[0].score, 4 * (2 + 3 + 5 + 6));
+    }
+
+    #[test]
+    fn diagonals_and_patterns() {
+        let numbers = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+        let rows: Vec<&[i32]> = numbers.iter().map(|row| &row[..]).collect();
+
+        let mut bingo = Bingo::new(vec![card(&rows)], &Rules::default().with_diagonals());
+        bingo.call(1);
+        bingo.call(5);
+        assert_eq!(bingo.call(9)[0].line, Line::Diagonal);
+
+        let mut bingo = Bingo::new(vec![card(&rows)], &Rules::default().with_corners());
+        bingo.call(1);
+        bingo.call(3);
+        bingo.call(7);
+        assert_eq!(bingo.call(9)[0].line, Line::Corners);
+
+        let rules = Rules::default().with_pattern(vec![(0, 1), (1, 0), (2, 2)]);
+        let mut bingo = Bingo::new(vec![card(&rows)], &rules);
+        bingo.call(2);
+        bingo.call(4);
+        assert_eq!(bingo.call(9)[0].line, Line::Custom(0));
+    }
+
+    #[test]
+    fn patterns_from_args() {
+        assert_eq!(parse_pattern("0,0/2,2"), Ok(vec![(0, 0), (2, 2)]));
+        assert_eq!(
+            parse_pattern("0,0/x,2"),
+            Err("Invalid cell in pattern: \"x,2\"".to_string())
+        );
+        assert_eq!(
+            parse_pattern("0,0/1"),
+            Err("Invalid cell in pattern: \"1\"".to_string())
+        );
+    }
+
+    #[test]
+    fn timeline() {
+        let (numbers, mut cards) = read_input("../../resources/test-input-dec-4").unwrap();
+        cards.push(card(&[&[90, 91], &[92, 93]]));
+        let timeline = simulate(numbers, cards, &Rules::default());
+
+        let order: Vec<(usize, usize, i32)> = timeline
+            .wins
+            .iter()
+            .map(|win| (win.card, win.draw, win.number))
+            .collect();
+        assert_eq!(order, vec![(2, 11, 24), (0, 13, 16), (1, 14, 13)]);
+        assert_eq!(timeline.wins[0].line, Line::Row(0));
+        assert_eq!(timeline.losers, vec![3]);
+    }
+
+    #[test]
+    fn parse_formatting_variants() {
+        let input = "1,2 , 3\r\n\r\n 1  2 3 \r\n 4  5 6\r\n\r\n\r\n7 8\r\n9 10";
+        let (numbers, cards) = parse_cards(input).unwrap();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(cards.len(), 2);
+        assert_eq!((cards[0].rows(), cards[0].cols()), (2, 3));
+        assert_eq!((cards[1].rows(), cards[1].cols()), (2, 2));
+        assert_eq!(cards[1].sum_unmarked(), 34);
+    }
 }
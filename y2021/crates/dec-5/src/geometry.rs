@@ -0,0 +1,122 @@
+use crate::line::step;
+use crate::{Cloud, Location};
+use std::collections::HashSet;
+
+/// A cloud as `start + t * direction` for `t` in `0..=length`, with `direction` the smallest
+/// lattice step so that every `t` is exactly one location of the cloud.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: (i64, i64),
+    direction: (i64, i64),
+    length: i64,
+    min_x: i64,
+    max_x: i64,
+}
+
+impl Segment {
+    fn new(cloud: &Cloud) -> Segment {
+        let start = (cloud.from.x as i64, cloud.from.y as i64);
+        let end = (cloud.to.x as i64, cloud.to.y as i64);
+        let direction = step(cloud.from, cloud.to);
+        let length = match direction {
+            (0, 0) => 0,
+            (dx, 0) => (end.0 - start.0) / dx,
+            (_, dy) => (end.1 - start.1) / dy,
+        };
+        Segment {
+            start,
+            direction,
+            length,
+            min_x: start.0.min(end.0),
+            max_x: start.0.max(end.0),
+        }
+    }
+
+    fn at(&self, t: i64) -> (i64, i64) {
+        (
+            self.start.0 + t * self.direction.0,
+            self.start.1 + t * self.direction.1,
+        )
+    }
+
+    /// The `t` at which the line through this segment passes exactly through `point`, if any.
+    fn parameter_of(&self, point: (i64, i64)) -> Option<i64> {
+        let offset = (point.0 - self.start.0, point.1 - self.start.1);
+        let t = match self.direction {
+            (0, 0) => return (offset == (0, 0)).then_some(0),
+            (dx, 0) => offset.0 / dx,
+            (_, dy) => offset.1 / dy,
+        };
+        (self.at(t) == point).then_some(t)
+    }
+
+    fn contains(&self, point: (i64, i64)) -> bool {
+        self.parameter_of(point)
+            .is_some_and(|t| (0..=self.length).contains(&t))
+    }
+}
+
+fn cross(a: (i64, i64), b: (i64, i64)) -> i64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+/// Adds every location covered by both `a` and `b` to `points`.
+fn intersect(a: &Segment, b: &Segment, points: &mut HashSet<(i64, i64)>) {
+    if b.length == 0 {
+        if a.contains(b.start) {
+            points.insert(b.start);
+        }
+        return;
+    }
+    if a.length == 0 {
+        return intersect(b, a, points);
+    }
+
+    let offset = (b.start.0 - a.start.0, b.start.1 - a.start.1);
+    let denominator = cross(a.direction, b.direction);
+    if denominator != 0 {
+        // The lines cross in a single point; it counts if it is a lattice point on both.
+        let t = cross(offset, b.direction);
+        let s = cross(offset, a.direction);
+        if t % denominator == 0 && s % denominator == 0 {
+            let (t, s) = (t / denominator, s / denominator);
+            if (0..=a.length).contains(&t) && (0..=b.length).contains(&s) {
+                points.insert(a.at(t));
+            }
+        }
+    } else if cross(offset, a.direction) == 0 {
+        // Collinear, and as both directions are primitive they are equal or opposite, so the
+        // overlap is a range of `t` on `a`.
+        if let Some(from) = a.parameter_of(b.start) {
+            let to = from + b.length * if a.direction == b.direction { 1 } else { -1 };
+            for t in from.min(to).max(0)..=from.max(to).min(a.length) {
+                points.insert(a.at(t));
+            }
+        }
+    }
+}
+
+/// All locations covered by at least two clouds, computed from the geometry of every pair of
+/// clouds instead of drawing them. Clouds are swept by `x` so only pairs that overlap on the
+/// `x` axis are compared.
+pub(crate) fn overlapping_points(clouds: &[Cloud]) -> HashSet<Location> {
+    let mut segments: Vec<Segment> = clouds.iter().map(Segment::new).collect();
+    segments.sort_unstable_by_key(|segment| segment.min_x);
+
+    let mut points: HashSet<(i64, i64)> = HashSet::new();
+    for (i, a) in segments.iter().enumerate() {
+        for b in segments[i + 1..].iter() {
+            if b.min_x > a.max_x {
+                break;
+            }
+            intersect(a, b, &mut points);
+        }
+    }
+    points
+        .into_iter()
+        .map(|(x, y)| Location {
+            x: x as usize,
+            y: y as usize,
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+//! The lines of hydrothermal vents, `x1,y1 -> x2,y2`.
+
+use std::fmt::{Display, Formatter};
+
+/// A point as `(x, y)`.
+pub type Point = (usize, usize);
+
+/// The largest coordinate, small enough for differences and their cross products to fit an
+/// `i64`.
+pub const MAX_COORDINATE: usize = i32::MAX as usize;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    MissingArrow { line: usize },
+    InvalidPoint { line: usize, value: String },
+    OutOfRange { line: usize, coordinate: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingArrow { line } => {
+                write!(f, "Line {}: expected two points joined by \" -> \"", line)
+            }
+            ParseError::InvalidPoint { line, value } => {
+                write!(f, "Line {}: {:?} is not a point like 0,9", line, value)
+            }
+            ParseError::OutOfRange { line, coordinate } => write!(
+                f,
+                "Line {}: {} is larger than {}",
+                line, coordinate, MAX_COORDINATE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A point `x,y`, errors are reported on `line`.
+pub fn parse_point(line: usize, text: &str) -> Result<Point, ParseError> {
+    let invalid = || ParseError::InvalidPoint {
+        line,
+        value: text.to_string(),
+    };
+    let (x, y) = text.split_once(',').ok_or_else(invalid)?;
+    let x = x.trim().parse::<usize>().map_err(|_| invalid())?;
+    let y = y.trim().parse::<usize>().map_err(|_| invalid())?;
+    if let Some(coordinate) = [x, y].into_iter().find(|&c| c > MAX_COORDINATE) {
+        return Err(ParseError::OutOfRange { line, coordinate });
+    }
+    Ok((x, y))
+}
+
+/// A line of vents `x1,y1 -> x2,y2`, errors are reported on `line`.
+pub fn parse_vent(line: usize, text: &str) -> Result<(Point, Point), ParseError> {
+    let (from, to) = text
+        .split_once("->")
+        .ok_or(ParseError::MissingArrow { line })?;
+    Ok((
+        parse_point(line, from.trim())?,
+        parse_point(line, to.trim())?,
+    ))
+}
+
+/// One line of vents per line, blank lines are skipped.
+pub fn parse_input(input: &str) -> Result<Vec<(Point, Point)>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line, text)| parse_vent(line, text))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, parse_point, ParseError, MAX_COORDINATE};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("0,9 -> 5,9\r\n\n8,0->0,8\n"),
+            Ok(vec![((0, 9), (5, 9)), ((8, 0), (0, 8))])
+        );
+        assert_eq!(
+            parse_input("0,9 -> 5,9\n0,9 5,9"),
+            Err(ParseError::MissingArrow { line: 2 })
+        );
+        assert_eq!(
+            parse_input("0,9 -> 5;9"),
+            Err(ParseError::InvalidPoint {
+                line: 1,
+                value: "5;9".to_string()
+            })
+        );
+        assert_eq!(
+            parse_point(3, "-1,2"),
+            Err(ParseError::InvalidPoint {
+                line: 3,
+                value: "-1,2".to_string()
+            })
+        );
+        assert_eq!(parse_point(1, "2147483647,0"), Ok((MAX_COORDINATE, 0)));
+        assert_eq!(
+            parse_point(2, "0,2147483648"),
+            Err(ParseError::OutOfRange {
+                line: 2,
+                coordinate: 2_147_483_648
+            })
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Cloud, Location};
+
+/// How a cloud is turned into map locations.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Rasterisation {
+    /// Only the locations exactly on the line.
+    #[default]
+    Lattice,
+    /// One location per column or row along the longest axis, like a line drawn on screen.
+    Bresenham,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Orientation {
+    Point,
+    Horizontal,
+    Vertical,
+    Diagonal,
+    Other,
+}
+
+impl Orientation {
+    pub(crate) fn of(cloud: &Cloud) -> Orientation {
+        let (dx, dy) = delta(cloud.from, cloud.to);
+        match (dx, dy) {
+            (0, 0) => Orientation::Point,
+            (_, 0) => Orientation::Horizontal,
+            (0, _) => Orientation::Vertical,
+            (dx, dy) if dx.abs() == dy.abs() => Orientation::Diagonal,
+            _ => Orientation::Other,
+        }
+    }
+
+    pub(crate) fn is_axis_aligned(&self) -> bool {
+        matches!(
+            self,
+            Orientation::Point | Orientation::Horizontal | Orientation::Vertical
+        )
+    }
+}
+
+fn delta(from: Location, to: Location) -> (i64, i64) {
+    (to.x as i64 - from.x as i64, to.y as i64 - from.y as i64)
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The smallest step from `from` towards `to` that lands on a location exactly on the line.
+pub(crate) fn step(from: Location, to: Location) -> (i64, i64) {
+    let (dx, dy) = delta(from, to);
+    match gcd(dx, dy) {
+        0 => (0, 0),
+        g => (dx / g, dy / g),
+    }
+}
+
+/// Iterator over the locations of a cloud, both end points included.
+pub(crate) struct Points {
+    x: i64,
+    y: i64,
+    span: (i64, i64),
+    step: (i64, i64),
+    error: i64,
+    remaining: u64,
+    mode: Rasterisation,
+}
+
+impl Points {
+    pub(crate) fn new(cloud: &Cloud, mode: Rasterisation) -> Points {
+        let (dx, dy) = delta(cloud.from, cloud.to);
+        let (step, remaining) = match mode {
+            Rasterisation::Lattice => (step(cloud.from, cloud.to), gcd(dx, dy) as u64 + 1),
+            Rasterisation::Bresenham => (
+                (dx.signum(), dy.signum()),
+                dx.abs().max(dy.abs()) as u64 + 1,
+            ),
+        };
+        Points {
+            x: cloud.from.x as i64,
+            y: cloud.from.y as i64,
+            span: (dx.abs(), -dy.abs()),
+            step,
+            error: dx.abs() - dy.abs(),
+            remaining,
+            mode,
+        }
+    }
+}
+
+impl Iterator for Points {
+    type Item = Location;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let location = Location {
+            x: self.x as usize,
+            y: self.y as usize,
+        };
+
+        match self.mode {
+            Rasterisation::Lattice => {
+                self.x += self.step.0;
+                self.y += self.step.1;
+            }
+            Rasterisation::Bresenham => {
+                let (dx, dy) = self.span;
+                let double = 2 * self.error;
+                if double >= dy {
+                    self.error += dy;
+                    self.x += self.step.0;
+                }
+                if double <= dx {
+                    self.error += dx;
+                    self.y += self.step.1;
+                }
+            }
+        }
+        Some(location)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl Cloud {
+    pub(crate) fn orientation(&self) -> Orientation {
+        Orientation::of(self)
+    }
+
+    pub(crate) fn points(&self, mode: Rasterisation) -> Points {
+        Points::new(self, mode)
+    }
+}
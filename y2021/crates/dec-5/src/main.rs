@@ -1,74 +1,35 @@
-use anstyle::Style;
-use grid::*;
-use std::fmt::{Display, Formatter};
-use std::fs::File;
+mod geometry;
+mod line;
+mod map;
+
+use dec_5::{parse_input, parse_point, parse_vent, ParseError, Point};
+use geometry::overlapping_points;
+use line::Rasterisation;
+use map::{map_for, print, VentMap};
+use std::fs;
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::str::FromStr;
+use std::time::Instant;
 
-#[derive(Debug, Default)]
-struct Thickness {
-    number: i32,
-}
-
-impl Display for Thickness {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let number = match self.number {
-            0 => {
-                let blue_style = Style::new()
-                    .fg_color(Some(anstyle::AnsiColor::Blue.into()))
-                    .bold();
-                let blue_style = blue_style.render();
-                format!("{}#", blue_style)
-            }
-            1 => {
-                let green_style = Style::new()
-                    .fg_color(Some(anstyle::AnsiColor::Green.into()))
-                    .bold();
-                let green_style = green_style.render();
-                format!("{}#", green_style)
-            }
-            _ => {
-                let red_style = Style::new()
-                    .fg_color(Some(anstyle::AnsiColor::Red.into()))
-                    .bold();
-                let red_style = red_style.render();
-                format!("{}#", red_style)
-            }
-        };
-        write!(f, "{}", number)
-    }
-}
-
-impl Thickness {
-    fn add_cloud(&mut self) {
-        self.number += 1;
-    }
-
-    fn cloudy(&self) -> bool {
-        self.number > 1
-    }
-}
-
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
 struct Location {
     x: usize,
     y: usize,
 }
 
+impl From<Point> for Location {
+    fn from((x, y): Point) -> Self {
+        Location { x, y }
+    }
+}
+
+/// A single point `x,y`, errors count it as line 1.
 impl FromStr for Location {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((x, y)) = s.split_once(',') {
-            Ok(Location {
-                x: x.parse::<usize>().unwrap(),
-                y: y.parse::<usize>().unwrap(),
-            })
-        } else {
-            panic!("Could not parse location.")
-        }
+        Ok(Location::from(parse_point(1, s)?))
     }
 }
 
@@ -81,153 +42,149 @@ struct Cloud {
 impl Iterator for Cloud {
     type Item = Cloud;
 
+    /// Moves `from` to the next location exactly on the line towards `to`.
     fn next(&mut self) -> Option<Self::Item> {
-        match &self {
-            cloud if cloud.from.x == cloud.to.x && cloud.from.y < cloud.to.y => {
-                self.from.y += 1;
-            }
-            cloud if cloud.from.x == cloud.to.x && cloud.from.y > cloud.to.y => {
-                self.from.y -= 1;
-            }
-            cloud if cloud.from.x < cloud.to.x && cloud.from.y == cloud.to.y => self.from.x += 1,
-            cloud if cloud.from.x > cloud.to.x && cloud.from.y == cloud.to.y => self.from.x -= 1,
-            cloud if cloud.from.x < cloud.to.x && cloud.from.y < cloud.to.y => {
-                self.from.x += 1;
-                self.from.y += 1;
-            }
-            cloud if cloud.from.x > cloud.to.x && cloud.from.y > cloud.to.y => {
-                self.from.x -= 1;
-                self.from.y -= 1;
-            }
-            cloud if cloud.from.x < cloud.to.x && cloud.from.y > cloud.to.y => {
-                self.from.x += 1;
-                self.from.y -= 1;
-            }
-            cloud if cloud.from.x > cloud.to.x && cloud.from.y < cloud.to.y => {
-                self.from.x -= 1;
-                self.from.y += 1;
-            }
-            _ => {
-                return None;
-            }
+        if self.from == self.to {
+            return None;
         }
+        let (dx, dy) = line::step(self.from, self.to);
+        self.from = Location {
+            x: (self.from.x as i64 + dx) as usize,
+            y: (self.from.y as i64 + dy) as usize,
+        };
         Some(*self)
     }
 }
 
+impl From<(Point, Point)> for Cloud {
+    fn from((from, to): (Point, Point)) -> Self {
+        Cloud {
+            from: Location::from(from),
+            to: Location::from(to),
+        }
+    }
+}
+
+/// A single line of vents `x1,y1 -> x2,y2`, errors count it as line 1.
 impl FromStr for Cloud {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((from, to)) = s.split_once(" -> ") {
-            Ok(Cloud {
-                from: from.parse::<Location>().unwrap(),
-                to: to.parse::<Location>().unwrap(),
-            })
-        } else {
-            panic!("Could not parse cloud.")
-        }
+        Ok(Cloud::from(parse_vent(1, s)?))
     }
 }
 
-fn print(grid: Grid<Thickness>) {
-    for row in 0..grid.rows() {
-        for number in grid.iter_row(row) {
-            print!("{}", number);
+fn main() {
+    let mut mode = Rasterisation::Lattice;
+    let mut geometry = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--bresenham" => mode = Rasterisation::Bresenham,
+            "--geometry" => geometry = true,
+            x => panic!("Unknown argument: {}", x),
         }
-        println!();
     }
-}
 
-fn main() {
     if let Ok(clouds) = read_input("./resources/input-dec-5") {
-        let map = process_clouds_1(clouds);
-        let cloudy_points = map.iter().filter(|location| location.cloudy()).count();
-        println!("Number of cloudy points: {}", cloudy_points);
+        let start = Instant::now();
+        let cloudy_points = if geometry {
+            let clouds: Vec<Cloud> = clouds
+                .into_iter()
+                .filter(|cloud| cloud.orientation().is_axis_aligned())
+                .collect();
+            overlapping_points(&clouds).len()
+        } else {
+            process_clouds_1(clouds, mode).cloudy_points()
+        };
+        println!(
+            "Number of cloudy points: {} ({:?})",
+            cloudy_points,
+            start.elapsed()
+        );
     }
 
     if let Ok(clouds) = read_input("./resources/input-dec-5") {
-        let map = process_clouds_2(clouds);
-        let cloudy_points = map.iter().filter(|location| location.cloudy()).count();
-        print(map);
-        println!("Number of cloudy points: {}", cloudy_points);
+        let start = Instant::now();
+        let cloudy_points = if geometry {
+            overlapping_points(&clouds).len()
+        } else {
+            let map = process_clouds_2(clouds, mode);
+            print(map.as_ref());
+            map.cloudy_points()
+        };
+        println!(
+            "Number of cloudy points: {} ({:?})",
+            cloudy_points,
+            start.elapsed()
+        );
     }
 }
 
-fn process_clouds_1(clouds: Vec<Cloud>) -> Grid<Thickness> {
-    let max = match clouds.iter().map(|cloud| cloud.from.max(cloud.to)).max() {
-        Some(location) => location.x.max(location.y),
-        None => {
-            panic!("No max found!")
-        }
-    };
-
-    let mut grid: Grid<Thickness> = Grid::new(max + 1, max + 1);
+fn draw_clouds(clouds: Vec<Cloud>, mode: Rasterisation) -> Box<dyn VentMap> {
+    let mut map = map_for(&clouds);
     for cloud in clouds {
-        if cloud.from.x == cloud.to.x || cloud.from.y == cloud.to.y {
-            if let Some(location) = grid.get_mut(cloud.from.y, cloud.from.x) {
-                location.add_cloud();
-            }
-            for cloud in cloud {
-                if let Some(location) = grid.get_mut(cloud.from.y, cloud.from.x) {
-                    location.add_cloud();
-                }
-            }
+        for location in cloud.points(mode) {
+            map.add_cloud(location);
         }
     }
-    grid
+    map
 }
 
-fn process_clouds_2(clouds: Vec<Cloud>) -> Grid<Thickness> {
-    let max = match clouds.iter().map(|cloud| cloud.from.max(cloud.to)).max() {
-        Some(location) => location.x.max(location.y),
-        None => {
-            panic!("No max found!")
-        }
-    };
+fn process_clouds_1(clouds: Vec<Cloud>, mode: Rasterisation) -> Box<dyn VentMap> {
+    draw_clouds(
+        clouds
+            .into_iter()
+            .filter(|cloud| cloud.orientation().is_axis_aligned())
+            .collect(),
+        mode,
+    )
+}
 
-    let mut grid: Grid<Thickness> = Grid::new(max + 1, max + 1);
-    for cloud in clouds {
-        if let Some(location) = grid.get_mut(cloud.from.y, cloud.from.x) {
-            location.add_cloud();
-        }
-        for cloud in cloud {
-            if let Some(location) = grid.get_mut(cloud.from.y, cloud.from.x) {
-                location.add_cloud();
-            }
-        }
-    }
-    grid
+fn process_clouds_2(clouds: Vec<Cloud>, mode: Rasterisation) -> Box<dyn VentMap> {
+    draw_clouds(clouds, mode)
 }
 
 fn read_input<P>(filename: P) -> io::Result<Vec<Cloud>>
 where
     P: AsRef<Path>,
 {
-    let mut clouds: Vec<Cloud> = Vec::new();
-
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
-
-    reader.lines().for_each(|line| {
-        if let Ok(line) = line {
-            clouds.push(line.parse::<Cloud>().unwrap());
-        }
-    });
-    Ok(clouds)
+    let input = fs::read_to_string(filename)?;
+    let vents =
+        parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Ok(vents.into_iter().map(Cloud::from).collect())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{print, process_clouds_1, process_clouds_2, read_input, Cloud, Location};
+    use crate::geometry::overlapping_points;
+    use crate::line::{Orientation, Rasterisation};
+    use crate::map::{map_for, print, Bounds, DenseMap, SparseMap, VentMap};
+    use crate::{draw_clouds, process_clouds_1, process_clouds_2, read_input, Cloud, Location};
+    use aoc::differential::{inputs, Differential, Shrink};
+    use aoc::y2021::vents;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    impl Shrink for Cloud {
+        fn shrink(&self) -> Vec<Self> {
+            let ends = ((self.from.x, self.from.y), (self.to.x, self.to.y));
+            ends.shrink()
+                .into_iter()
+                .map(|((x1, y1), (x2, y2))| Cloud {
+                    from: Location { x: x1, y: y1 },
+                    to: Location { x: x2, y: y2 },
+                })
+                .collect()
+        }
+    }
 
     #[test]
     fn solution_1() {
         match read_input("../../resources/test-input-dec-5") {
             Ok(clouds) => {
-                let map = process_clouds_1(clouds);
-                let cloudy_points = map.iter().filter(|location| location.cloudy()).count();
-                print(map);
+                let map = process_clouds_1(clouds, Rasterisation::Lattice);
+                let cloudy_points = map.cloudy_points();
+                print(map.as_ref());
                 println!("Number of cloudy points: {}", cloudy_points);
                 assert_eq!(cloudy_points, 5)
             }
@@ -242,9 +199,9 @@ mod tests {
     fn solution_2() {
         match read_input("../../resources/test-input-dec-5") {
             Ok(clouds) => {
-                let map = process_clouds_2(clouds);
-                let cloudy_points = map.iter().filter(|location| location.cloudy()).count();
-                print(map);
+                let map = process_clouds_2(clouds, Rasterisation::Lattice);
+                let cloudy_points = map.cloudy_points();
+                print(map.as_ref());
                 println!("Number of cloudy points: {}", cloudy_points);
                 assert_eq!(cloudy_points, 12)
             }
@@ -280,4 +237,203 @@ mod tests {
         assert_eq!(cloud.next().unwrap(), cloud_3);
         assert_eq!(cloud.next(), None);
     }
+
+    #[test]
+    fn sparse_far_away() {
+        let clouds: Vec<Cloud> = [
+            "0,0 -> 2,2",
+            "1000000,0 -> 1000000,20",
+            "999990,10 -> 1000010,10",
+        ]
+        .iter()
+        .map(|line| line.parse::<Cloud>().unwrap())
+        .collect();
+        let map = draw_clouds(clouds, Rasterisation::Lattice);
+        assert_eq!(map.cloudy_points(), 1);
+        assert!(map
+            .thickness(Location {
+                x: 1_000_000,
+                y: 10
+            })
+            .cloudy());
+    }
+
+    #[test]
+    fn huge_bounds() {
+        let clouds = vec![Cloud {
+            from: Location { x: 0, y: 3 },
+            to: Location {
+                x: usize::MAX,
+                y: 3,
+            },
+        }];
+        let bounds = Bounds::of(&clouds).unwrap();
+        assert_eq!(bounds.width(), None);
+        assert_eq!(bounds.height(), Some(1));
+        assert_eq!(bounds.cells(), None);
+        assert!(DenseMap::new(bounds).is_none());
+
+        let mut map = map_for(&clouds);
+        map.add_cloud(Location { x: 0, y: 3 });
+        map.add_cloud(Location { x: 0, y: 3 });
+        map.add_cloud(Location {
+            x: usize::MAX,
+            y: 3,
+        });
+        assert_eq!(map.cloudy_points(), 1);
+        // Sparse and too large to draw, so this prints nothing
+        print(map.as_ref());
+    }
+
+    #[test]
+    fn backends_agree() {
+        let clouds = read_input("../../resources/test-input-dec-5").unwrap();
+        let bounds = Bounds::of(&clouds).unwrap();
+        let mut dense = DenseMap::new(bounds).unwrap();
+        let mut sparse = SparseMap::default();
+        for cloud in clouds {
+            for map in [&mut dense as &mut dyn VentMap, &mut sparse] {
+                map.add_cloud(cloud.from);
+                for cloud in cloud {
+                    map.add_cloud(cloud.from);
+                }
+            }
+        }
+        assert_eq!(dense.cloudy_points(), 12);
+        assert_eq!(sparse.cloudy_points(), 12);
+        assert_eq!(dense.bounds(), sparse.bounds());
+    }
+
+    #[test]
+    fn arbitrary_slopes() {
+        let cloud: Cloud = "0,0 -> 6,4".parse().unwrap();
+        assert_eq!(cloud.orientation(), Orientation::Other);
+
+        let lattice: Vec<Location> = cloud.points(Rasterisation::Lattice).collect();
+        assert_eq!(
+            lattice,
+            vec![
+                Location { x: 0, y: 0 },
+                Location { x: 3, y: 2 },
+                Location { x: 6, y: 4 }
+            ]
+        );
+        assert_eq!(
+            cloud.map(|c| c.from).collect::<Vec<Location>>(),
+            lattice[1..]
+        );
+
+        let bresenham: Vec<(usize, usize)> = cloud
+            .points(Rasterisation::Bresenham)
+            .map(|l| (l.x, l.y))
+            .collect();
+        assert_eq!(
+            bresenham,
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 3), (5, 3), (6, 4)]
+        );
+
+        let back: Cloud = "6,4 -> 0,0".parse().unwrap();
+        let mut reversed: Vec<(usize, usize)> = back
+            .points(Rasterisation::Bresenham)
+            .map(|l| (l.x, l.y))
+            .collect();
+        reversed.reverse();
+        assert_eq!(reversed, bresenham);
+    }
+
+    #[test]
+    fn geometry_far_out() {
+        let clouds: Vec<Cloud> = [
+            "0,0 -> 2147483646,2147483646",
+            "0,2147483646 -> 2147483646,0",
+            "2147483647,0 -> 2147483647,2147483647",
+            "2147483647,5 -> 2147483647,9",
+        ]
+        .iter()
+        .map(|line| line.parse::<Cloud>().unwrap())
+        .collect();
+        let mut points: Vec<Location> = overlapping_points(&clouds).into_iter().collect();
+        points.sort_by_key(|location| (location.x, location.y));
+        assert_eq!(points.len(), 6);
+        assert_eq!(
+            points[0],
+            Location {
+                x: 1_073_741_823,
+                y: 1_073_741_823
+            }
+        );
+    }
+
+    #[test]
+    fn geometry_matches_raster() {
+        let clouds = read_input("../../resources/test-input-dec-5").unwrap();
+        assert_eq!(overlapping_points(&clouds).len(), 12);
+
+        // Pseudo random clouds of every orientation, including points and overlapping lines.
+        let mut rng = StdRng::seed_from_u64(2021);
+        let mut random = |max: usize| rng.gen_range(0..max);
+        let mut clouds: Vec<Cloud> = Vec::new();
+        for _ in 0..300 {
+            let from = Location {
+                x: random(40),
+                y: random(40),
+            };
+            let to = match random(4) {
+                0 => Location {
+                    x: from.x,
+                    y: random(40),
+                },
+                1 => Location {
+                    x: random(40),
+                    y: from.y,
+                },
+                2 => {
+                    let d = random(20);
+                    Location {
+                        x: from.x + d,
+                        y: from.y.saturating_sub(d),
+                    }
+                }
+                _ => Location {
+                    x: random(40),
+                    y: random(40),
+                },
+            };
+            clouds.push(Cloud { from, to });
+        }
+
+        let raster = draw_clouds(clouds.clone(), Rasterisation::Lattice);
+        let geometry = overlapping_points(&clouds);
+        assert_eq!(geometry.len(), raster.cloudy_points());
+        assert!(geometry
+            .iter()
+            .all(|&location| raster.thickness(location).cloudy()));
+    }
+
+    #[test]
+    fn differential() {
+        let fill = |mut map: Box<dyn VentMap>, clouds: &[Cloud]| {
+            for cloud in clouds {
+                for location in cloud.points(Rasterisation::Lattice) {
+                    map.add_cloud(location);
+                }
+            }
+            map.cloudy_points()
+        };
+        let bounds = |clouds: &[Cloud]| crate::map::Bounds::of(clouds).unwrap();
+        Differential::new("geometry", |clouds: &Vec<Cloud>| {
+            overlapping_points(clouds).len()
+        })
+        .register("dense", |clouds| {
+            fill(Box::new(DenseMap::new(bounds(clouds)).unwrap()), clouds)
+        })
+        .register("sparse", |clouds| fill(Box::<SparseMap>::default(), clouds))
+        .only(|clouds| !clouds.is_empty())
+        .assert(inputs(50, 5, |rng| {
+            vents(rng, 30, 40)
+                .lines()
+                .map(|line| line.parse::<Cloud>().unwrap())
+                .collect()
+        }));
+    }
 }
@@ -0,0 +1,215 @@
+use crate::{Cloud, Location};
+use anstyle::Style;
+use grid::Grid;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Maps covering more cells than this are kept sparse.
+const DENSE_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Thickness {
+    number: i32,
+}
+
+impl Display for Thickness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let number = match self.number {
+            0 => {
+                let blue_style = Style::new()
+                    .fg_color(Some(anstyle::AnsiColor::Blue.into()))
+                    .bold();
+                let blue_style = blue_style.render();
+                format!("{}#", blue_style)
+            }
+            1 => {
+                let green_style = Style::new()
+                    .fg_color(Some(anstyle::AnsiColor::Green.into()))
+                    .bold();
+                let green_style = green_style.render();
+                format!("{}#", green_style)
+            }
+            _ => {
+                let red_style = Style::new()
+                    .fg_color(Some(anstyle::AnsiColor::Red.into()))
+                    .bold();
+                let red_style = red_style.render();
+                format!("{}#", red_style)
+            }
+        };
+        write!(f, "{}", number)
+    }
+}
+
+impl Thickness {
+    fn add_cloud(&mut self) {
+        self.number += 1;
+    }
+
+    pub fn cloudy(&self) -> bool {
+        self.number > 1
+    }
+}
+
+/// The smallest rectangle containing every end point of a set of clouds.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Bounds {
+    pub min: Location,
+    pub max: Location,
+}
+
+impl Bounds {
+    pub fn of(clouds: &[Cloud]) -> Option<Bounds> {
+        Bounds::around(clouds.iter().flat_map(|cloud| [cloud.from, cloud.to]))
+    }
+
+    pub fn around<I>(points: I) -> Option<Bounds>
+    where
+        I: IntoIterator<Item = Location>,
+    {
+        points.into_iter().fold(None, |bounds, point| match bounds {
+            None => Some(Bounds {
+                min: point,
+                max: point,
+            }),
+            Some(Bounds { min, max }) => Some(Bounds {
+                min: Location {
+                    x: min.x.min(point.x),
+                    y: min.y.min(point.y),
+                },
+                max: Location {
+                    x: max.x.max(point.x),
+                    y: max.y.max(point.y),
+                },
+            }),
+        })
+    }
+
+    /// `None` when the rectangle spans every `usize`.
+    pub fn width(&self) -> Option<usize> {
+        (self.max.x - self.min.x).checked_add(1)
+    }
+
+    pub fn height(&self) -> Option<usize> {
+        (self.max.y - self.min.y).checked_add(1)
+    }
+
+    /// Number of cells in the rectangle, `None` if that does not even fit a `usize`.
+    pub fn cells(&self) -> Option<usize> {
+        self.width()?.checked_mul(self.height()?)
+    }
+
+    /// Small enough to keep every cell in memory, or on screen.
+    pub fn is_small(&self) -> bool {
+        self.cells().is_some_and(|cells| cells <= DENSE_LIMIT)
+    }
+
+    pub fn contains(&self, location: Location) -> bool {
+        (self.min.x..=self.max.x).contains(&location.x)
+            && (self.min.y..=self.max.y).contains(&location.y)
+    }
+}
+
+/// Storage for the number of clouds on every location.
+pub trait VentMap {
+    fn add_cloud(&mut self, location: Location);
+    fn thickness(&self, location: Location) -> Thickness;
+    fn cloudy_points(&self) -> usize;
+    fn bounds(&self) -> Option<Bounds>;
+}
+
+/// A grid covering the bounding box, indexed relative to its top left corner.
+pub struct DenseMap {
+    grid: Grid<Thickness>,
+    bounds: Bounds,
+}
+
+impl DenseMap {
+    /// `None` when the bounds are too large for a grid.
+    pub fn new(bounds: Bounds) -> Option<DenseMap> {
+        if !bounds.is_small() {
+            return None;
+        }
+        Some(DenseMap {
+            grid: Grid::new(bounds.height()?, bounds.width()?),
+            bounds,
+        })
+    }
+}
+
+impl VentMap for DenseMap {
+    fn add_cloud(&mut self, location: Location) {
+        if self.bounds.contains(location) {
+            let (row, col) = (
+                location.y - self.bounds.min.y,
+                location.x - self.bounds.min.x,
+            );
+            if let Some(thickness) = self.grid.get_mut(row, col) {
+                thickness.add_cloud();
+            }
+        }
+    }
+
+    fn thickness(&self, location: Location) -> Thickness {
+        if !self.bounds.contains(location) {
+            return Thickness::default();
+        }
+        let (row, col) = (
+            location.y - self.bounds.min.y,
+            location.x - self.bounds.min.x,
+        );
+        self.grid.get(row, col).copied().unwrap_or_default()
+    }
+
+    fn cloudy_points(&self) -> usize {
+        self.grid.iter().filter(|t| t.cloudy()).count()
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        Some(self.bounds)
+    }
+}
+
+/// Only stores the locations that have at least one cloud.
+#[derive(Default)]
+pub struct SparseMap {
+    clouds: HashMap<Location, Thickness>,
+}
+
+impl VentMap for SparseMap {
+    fn add_cloud(&mut self, location: Location) {
+        self.clouds.entry(location).or_default().add_cloud();
+    }
+
+    fn thickness(&self, location: Location) -> Thickness {
+        self.clouds.get(&location).copied().unwrap_or_default()
+    }
+
+    fn cloudy_points(&self) -> usize {
+        self.clouds.values().filter(|t| t.cloudy()).count()
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::around(self.clouds.keys().copied())
+    }
+}
+
+/// Picks a dense grid when the bounding box of `clouds` is small enough, a sparse map otherwise.
+pub fn map_for(clouds: &[Cloud]) -> Box<dyn VentMap> {
+    match Bounds::of(clouds).and_then(DenseMap::new) {
+        Some(map) => Box::new(map),
+        None => Box::<SparseMap>::default(),
+    }
+}
+
+/// Draws the bounding box of the map, unless it has too many cells to show.
+pub fn print(map: &dyn VentMap) {
+    if let Some(bounds) = map.bounds().filter(Bounds::is_small) {
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                print!("{}", map.thickness(Location { x, y }));
+            }
+            println!();
+        }
+    }
+}
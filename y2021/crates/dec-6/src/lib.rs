@@ -0,0 +1,67 @@
+//! The timers of the lanternfish.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    InvalidTimer { line: usize, value: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidTimer { line, value } => {
+                write!(f, "Line {}: {:?} is not a timer from 0 to 255", line, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The days until a fish gives birth, errors are reported on `line`.
+pub fn parse_timer(line: usize, text: &str) -> Result<u8, ParseError> {
+    let text = text.trim();
+    text.parse::<u8>().map_err(|_| ParseError::InvalidTimer {
+        line,
+        value: text.to_string(),
+    })
+}
+
+/// Comma separated timers, on any number of lines. Blank lines are skipped.
+pub fn parse_input(input: &str) -> Result<Vec<u8>, ParseError> {
+    let mut timers: Vec<u8> = Vec::new();
+    for (line, text) in input.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if text.is_empty() {
+            continue;
+        }
+        for timer in text.split(',') {
+            timers.push(parse_timer(line, timer)?);
+        }
+    }
+    Ok(timers)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_input("3,4, 3\r\n\n1,2\n"), Ok(vec![3, 4, 3, 1, 2]));
+        assert_eq!(
+            parse_input("3,4\n3,,1"),
+            Err(ParseError::InvalidTimer {
+                line: 2,
+                value: "".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("256"),
+            Err(ParseError::InvalidTimer {
+                line: 1,
+                value: "256".to_string()
+            })
+        );
+    }
+}
@@ -1,45 +1,128 @@
+mod population;
+
+use dec_6::{parse_input, parse_timer, ParseError};
+use num_bigint::BigUint;
+use population::{Lifecycle, Population};
 use std::collections::HashMap;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::str::FromStr;
 
+/// Runs `cargo run -p dec-6 -- [nights] [--modulus M] [--linear | --naive]`, by default for 80
+/// and 256 nights. The count comes from the matrix power unless `--linear` asks for night by
+/// night or `--naive` for simulating every fish on its own. `--newborn N`, `--reset N` and
+/// `--mortality K` change the lifecycle, `--csv FILE` writes the buckets of every night.
 fn main() {
+    let mut nights: Vec<u64> = Vec::new();
+    let mut lifecycle = Lifecycle::default();
+    let mut csv: Option<String> = None;
+    let mut modulus: Option<BigUint> = None;
+    let mut linear = false;
+    let mut naive = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--linear" => linear = true,
+            "--naive" => naive = true,
+            "--newborn" => lifecycle.newborn = timer_arg(args.next(), "--newborn"),
+            "--reset" => lifecycle.reset = timer_arg(args.next(), "--reset"),
+            "--mortality" => lifecycle.mortality = Some(number_arg(args.next(), "--mortality")),
+            "--csv" => csv = args.next(),
+            "--modulus" => {
+                modulus = args.next().and_then(|m| m.parse::<BigUint>().ok());
+                assert!(modulus.is_some(), "--modulus needs a number");
+            }
+            x => nights.push(x.parse::<u64>().expect("Nights should be a number")),
+        }
+    }
+    if nights.is_empty() {
+        nights = vec![80, 256]; // expect: for 80: 353079 for 256:1605400130036
+    }
+
     if let Ok(fishes) = read_input("./resources/input-dec-6") {
-        let mut fishes_counted: u64 = 0;
-        let mut precounted: HashMap<u8, u64> = HashMap::new();
-        for fish in fishes {
-            let fishes = precounted
-                .entry(fish.timer_to_birth)
-                .or_insert_with(|| fish.night_quick(256));
-            fishes_counted += *fishes
+        let population = Population::new(&fishes, lifecycle);
+        if let Some(csv) = csv {
+            let mut file = File::create(csv).expect("Could not create the CSV file");
+            let last = nights.iter().max().copied().unwrap_or_default();
+            population
+                .write_csv(last, &mut file)
+                .expect("Could not write the CSV file");
+        }
+        for nights in nights.iter().copied() {
+            let fishes = if naive {
+                BigUint::from(precounted(&fishes, nights, &lifecycle))
+            } else if linear {
+                let mut population = population.clone();
+                population.simulate(nights);
+                match &modulus {
+                    Some(modulus) => population.count() % modulus,
+                    None => population.count(),
+                }
+            } else {
+                population.count_after(nights, modulus.as_ref())
+            };
+            println!("Fishes after {} nights: {}", nights, fishes)
         }
-        println!("Fishes: {}", fishes_counted) // expect: for 80: 353079 for 256:1605400130036
     }
 }
 
+/// Simulates every fish on its own, once per starting timer.
+fn precounted(fishes: &[Fish], nights: u64, lifecycle: &Lifecycle) -> u64 {
+    let mut precounted: HashMap<u8, u64> = HashMap::new();
+    let mut fishes_counted: u64 = 0;
+    for fish in fishes.iter() {
+        fishes_counted += *precounted
+            .entry(fish.timer_to_birth)
+            .or_insert_with(|| fish.night_quick(nights, lifecycle));
+    }
+    fishes_counted
+}
+
+fn number_arg(arg: Option<String>, name: &str) -> usize {
+    arg.and_then(|n| n.parse::<usize>().ok())
+        .unwrap_or_else(|| panic!("{} needs a number", name))
+}
+
+fn timer_arg(arg: Option<String>, name: &str) -> u8 {
+    arg.and_then(|n| n.parse::<u8>().ok())
+        .unwrap_or_else(|| panic!("{} needs a timer from 0 to 255", name))
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Fish {
     timer_to_birth: u8,
+    births: usize,
 }
 
 impl Fish {
-    fn new() -> Fish {
-        Fish { timer_to_birth: 8 }
+    fn new(lifecycle: &Lifecycle) -> Fish {
+        Fish {
+            timer_to_birth: lifecycle.newborn,
+            births: 0,
+        }
     }
 
-    fn reset(&mut self) {
-        self.timer_to_birth = 6;
+    fn reset(&mut self, lifecycle: &Lifecycle) {
+        self.timer_to_birth = lifecycle.reset;
+        self.births += 1;
     }
 
-    fn night(&mut self) -> Vec<Fish> {
+    fn alive(&self, lifecycle: &Lifecycle) -> bool {
+        lifecycle
+            .mortality
+            .is_none_or(|mortality| self.births < mortality)
+    }
+
+    fn night(&mut self, lifecycle: &Lifecycle) -> Vec<Fish> {
         let mut fishes: Vec<Fish> = Vec::new();
         match self.timer_to_birth {
             0 => {
-                self.reset();
-                fishes.push(*self);
-                fishes.push(Fish::new())
+                self.reset(lifecycle);
+                if self.alive(lifecycle) {
+                    fishes.push(*self);
+                }
+                fishes.push(Fish::new(lifecycle))
             }
             _ => {
                 self.timer_to_birth -= 1;
@@ -49,36 +132,27 @@ impl Fish {
         fishes
     }
 
-    fn night_quick(&self, nights: u64) -> u64 {
+    fn night_quick(&self, nights: u64, lifecycle: &Lifecycle) -> u64 {
         let mut fishes = vec![*self];
         for _ in 0..nights {
             let mut new_fishes: Vec<Fish> = Vec::new();
             for fish in fishes.iter_mut() {
-                new_fishes.append(&mut fish.night());
+                new_fishes.append(&mut fish.night(lifecycle));
             }
             fishes = new_fishes;
         }
         fishes.len() as u64
     }
-
-    fn quick_256_night(&mut self) -> u64 {
-        match self.timer_to_birth {
-            1 => 6206821033,
-            2 => 5617089148,
-            3 => 5217223242,
-            4 => 4726100874,
-            5 => 4368232009,
-            _ => panic!("Wrong number"),
-        }
-    }
 }
 
+/// A single timer, errors count it as line 1.
 impl FromStr for Fish {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            timer_to_birth: s.parse::<u8>().unwrap(),
+            timer_to_birth: parse_timer(1, s)?,
+            births: 0,
         })
     }
 }
@@ -87,66 +161,212 @@ fn read_input<P>(filename: P) -> io::Result<Vec<Fish>>
 where
     P: AsRef<Path>,
 {
-    let mut fishes: Vec<Fish> = Vec::new();
-
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
-
-    reader.lines().for_each(|line| {
-        if let Ok(line) = line {
-            let mut new_fish: Vec<Fish> = line
-                .split(',')
-                .map(|s| s.parse::<Fish>().unwrap())
-                .collect();
-            fishes.append(&mut new_fish);
-        }
-    });
-    Ok(fishes)
+    let input = fs::read_to_string(filename)?;
+    let timers =
+        parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Ok(timers
+        .into_iter()
+        .map(|timer_to_birth| Fish {
+            timer_to_birth,
+            births: 0,
+        })
+        .collect())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{read_input, Fish};
+    use crate::population::{Lifecycle, Population};
+    use crate::{precounted, read_input, Fish};
+    use aoc::differential::{inputs, Differential, Shrink};
+    use aoc::y2021::fish;
+    use num_bigint::BigUint;
+    use rand::Rng;
     use std::collections::HashMap;
 
     #[test]
     fn solution_1() {
-        if let Ok(mut fishes) = read_input("../../resources/test-input-dec-6") {
-            for _ in 0..80 {
-                let mut new_fishes: Vec<Fish> = Vec::new();
-                for fish in fishes.iter_mut() {
-                    new_fishes.append(&mut fish.night());
-                }
-                fishes = new_fishes;
+        let mut fishes = read_input("../../resources/test-input-dec-6").unwrap();
+        for _ in 0..80 {
+            let mut new_fishes: Vec<Fish> = Vec::new();
+            for fish in fishes.iter_mut() {
+                new_fishes.append(&mut fish.night(&Lifecycle::default()));
             }
-            let fishes = fishes.len();
-            assert_eq!(fishes, 5934)
+            fishes = new_fishes;
         }
+        let fishes = fishes.len();
+        assert_eq!(fishes, 5934)
     }
 
     #[test]
     fn solution_2() {
-        if let Ok(fishes) = read_input("../../resources/test-input-dec-6") {
-            let mut fishes_counted: u64 = 0;
-            for mut fish in fishes {
-                fishes_counted += fish.quick_256_night();
-            }
-            assert_eq!(fishes_counted, 26984457539)
-        }
+        let fishes = read_input("../../resources/test-input-dec-6").unwrap();
+        let population = Population::new(&fishes, Lifecycle::default());
+        assert_eq!(
+            population.count_after(256, None),
+            BigUint::from(26984457539u64)
+        )
     }
 
     #[test]
     fn solution_1_new() {
-        if let Ok(fishes) = read_input("../../resources/test-input-dec-6") {
-            let mut fishes_counted: u64 = 0;
-            let mut precounted: HashMap<u8, u64> = HashMap::new();
-            for fish in fishes {
-                let fishes = precounted
-                    .entry(fish.timer_to_birth)
-                    .or_insert(fish.night_quick(80));
-                fishes_counted += *fishes
-            }
-            assert_eq!(fishes_counted, 5934)
+        let fishes = read_input("../../resources/test-input-dec-6").unwrap();
+        let mut fishes_counted: u64 = 0;
+        let mut precounted: HashMap<u8, u64> = HashMap::new();
+        for fish in fishes {
+            let fishes = precounted
+                .entry(fish.timer_to_birth)
+                .or_insert(fish.night_quick(80, &Lifecycle::default()));
+            fishes_counted += *fishes
         }
+        assert_eq!(fishes_counted, 5934)
+    }
+
+    #[test]
+    fn linear() {
+        let fishes = read_input("../../resources/test-input-dec-6").unwrap();
+        let mut population = Population::new(&fishes, Lifecycle::default());
+        population.simulate(18);
+        assert_eq!(population.count(), BigUint::from(26u32));
+        population.simulate(80 - 18);
+        assert_eq!(population.count(), BigUint::from(5934u32))
+    }
+
+    #[test]
+    fn linear_matches_matrix() {
+        let fishes: Vec<Fish> = [3, 4, 3, 1, 2]
+            .iter()
+            .map(|&t| Fish {
+                timer_to_birth: t,
+                births: 0,
+            })
+            .collect();
+        let population = Population::new(&fishes, Lifecycle::default());
+        let mut linear = population.clone();
+        linear.simulate(1000);
+        assert_eq!(linear.count(), population.count_after(1000, None));
+
+        let modulus = BigUint::from(1_000_000_007u32);
+        assert_eq!(
+            linear.count() % &modulus,
+            population.count_after(1000, Some(&modulus))
+        );
+        assert!(population.count_after(1_000_000_000_000_000_000, Some(&modulus)) < modulus);
+    }
+
+    #[test]
+    fn other_lifecycles() {
+        let fishes: Vec<Fish> = [3, 4, 3, 1, 2]
+            .iter()
+            .map(|&t| Fish {
+                timer_to_birth: t,
+                births: 0,
+            })
+            .collect();
+        for lifecycle in [
+            Lifecycle {
+                newborn: 4,
+                reset: 2,
+                mortality: None,
+            },
+            Lifecycle {
+                newborn: 3,
+                reset: 5,
+                mortality: Some(2),
+            },
+            Lifecycle {
+                newborn: 8,
+                reset: 6,
+                mortality: Some(1),
+            },
+        ] {
+            let naive: u64 = fishes.iter().map(|f| f.night_quick(40, &lifecycle)).sum();
+            let population = Population::new(&fishes, lifecycle);
+            let mut linear = population.clone();
+            linear.simulate(40);
+            assert_eq!(linear.count(), BigUint::from(naive));
+            assert_eq!(population.count_after(40, None), BigUint::from(naive));
+        }
+    }
+
+    #[test]
+    fn many_births() {
+        let lifecycle = Lifecycle {
+            mortality: Some(300),
+            ..Lifecycle::default()
+        };
+        let mut fish = Fish::new(&lifecycle);
+        for _ in 0..299 {
+            fish.reset(&lifecycle);
+        }
+        assert!(fish.alive(&lifecycle));
+        fish.reset(&lifecycle);
+        assert!(!fish.alive(&lifecycle));
+    }
+
+    #[test]
+    fn csv() {
+        let fishes = vec![Fish {
+            timer_to_birth: 1,
+            births: 0,
+        }];
+        let population = Population::new(&fishes, Lifecycle::default());
+        let mut csv: Vec<u8> = Vec::new();
+        population.write_csv(2, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "night,timer_0,timer_1,timer_2,timer_3,timer_4,timer_5,timer_6,timer_7,timer_8\n\
+             0,0,1,0,0,0,0,0,0,0\n\
+             1,1,0,0,0,0,0,0,0,0\n\
+             2,0,0,0,0,0,0,1,0,1\n"
+        );
+    }
+
+    impl Shrink for Lifecycle {}
+
+    #[test]
+    fn differential() {
+        let fishes = |timers: &[u8]| -> Vec<Fish> {
+            timers
+                .iter()
+                .map(|&timer| Fish {
+                    timer_to_birth: timer,
+                    births: 0,
+                })
+                .collect()
+        };
+        type Input = (Lifecycle, (Vec<u8>, u8));
+        Differential::new("fish by fish", |(lifecycle, (timers, nights)): &Input| {
+            let total: u64 = fishes(timers)
+                .iter()
+                .map(|fish| fish.night_quick(*nights as u64, lifecycle))
+                .sum();
+            BigUint::from(total)
+        })
+        .register("precounted", |(lifecycle, (timers, nights))| {
+            BigUint::from(precounted(&fishes(timers), *nights as u64, lifecycle))
+        })
+        .register("night by night", |(lifecycle, (timers, nights))| {
+            let mut population = Population::new(&fishes(timers), *lifecycle);
+            population.simulate(*nights as u64);
+            population.count()
+        })
+        .register("matrix power", |(lifecycle, (timers, nights))| {
+            Population::new(&fishes(timers), *lifecycle).count_after(*nights as u64, None)
+        })
+        .only(|(_, (timers, nights))| *nights <= 40 && timers.iter().all(|&timer| timer <= 8))
+        .assert(inputs(30, 6, |rng| {
+            // Timers of at least two keep the naive simulation from growing too fast
+            let lifecycle = match rng.gen_range(0..3) {
+                0 => Lifecycle::default(),
+                _ => Lifecycle {
+                    newborn: rng.gen_range(2..=8),
+                    reset: rng.gen_range(2..=8),
+                    mortality: rng.gen_bool(0.5).then(|| rng.gen_range(1..=3)),
+                },
+            };
+            let timers = fish(rng, 20, 8).unwrap();
+            let timers = timers.trim().split(',').map(|t| t.parse().unwrap());
+            (lifecycle, (timers.collect(), rng.gen_range(0..=40)))
+        }));
     }
 }
@@ -0,0 +1,219 @@
+use crate::Fish;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use std::io;
+use std::io::Write;
+
+/// The life of a lanternfish: the timer it is born with, the timer it gets after giving birth
+/// and, optionally, after how many births it dies.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct Lifecycle {
+    pub(crate) newborn: u8,
+    pub(crate) reset: u8,
+    pub(crate) mortality: Option<usize>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Lifecycle {
+            newborn: 8,
+            reset: 6,
+            mortality: None,
+        }
+    }
+}
+
+impl Lifecycle {
+    /// Number of birth counts that have to be told apart, only more than one with mortality.
+    fn generations(&self) -> usize {
+        self.mortality.unwrap_or(1).max(1)
+    }
+
+    /// Where a fish that gave birth ends up, `None` if that was its last birth.
+    fn after_birth(&self, births: usize) -> Option<(usize, usize)> {
+        match self.mortality {
+            Some(mortality) if births + 1 >= mortality => None,
+            Some(_) => Some((births + 1, usize::from(self.reset))),
+            None => Some((births, usize::from(self.reset))),
+        }
+    }
+}
+
+/// Square matrix of big integers, optionally reduced by a modulus after every operation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Matrix {
+    cells: Vec<Vec<BigUint>>,
+}
+
+impl Matrix {
+    fn identity(size: usize) -> Matrix {
+        let mut cells = vec![vec![BigUint::zero(); size]; size];
+        for (i, row) in cells.iter_mut().enumerate() {
+            row[i] = BigUint::one();
+        }
+        Matrix { cells }
+    }
+
+    fn multiply(&self, other: &Matrix, modulus: Option<&BigUint>) -> Matrix {
+        let size = self.cells.len();
+        let mut cells = vec![vec![BigUint::zero(); size]; size];
+        for (i, row) in cells.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                for k in 0..size {
+                    if !self.cells[i][k].is_zero() && !other.cells[k][j].is_zero() {
+                        *cell += &self.cells[i][k] * &other.cells[k][j];
+                    }
+                }
+                if let Some(modulus) = modulus {
+                    *cell %= modulus;
+                }
+            }
+        }
+        Matrix { cells }
+    }
+
+    /// `self` to the power `exponent` by repeated squaring.
+    fn pow(&self, mut exponent: u64, modulus: Option<&BigUint>) -> Matrix {
+        let mut result = Matrix::identity(self.cells.len());
+        let mut square = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply(&square, modulus);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.multiply(&square, modulus);
+            }
+        }
+        result
+    }
+
+    fn apply(&self, vector: &[BigUint], modulus: Option<&BigUint>) -> Vec<BigUint> {
+        self.cells
+            .iter()
+            .map(|row| {
+                let sum: BigUint = row.iter().zip(vector).map(|(a, b)| a * b).sum();
+                match modulus {
+                    Some(modulus) => sum % modulus,
+                    None => sum,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A school of lanternfish as the number of fish per `(births, timer)` bucket. Without
+/// mortality the births do not matter and there is a bucket per timer only.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Population {
+    lifecycle: Lifecycle,
+    timers: usize,
+    buckets: Vec<BigUint>,
+}
+
+impl Population {
+    pub(crate) fn new(fishes: &[Fish], lifecycle: Lifecycle) -> Population {
+        let highest = fishes
+            .iter()
+            .map(|fish| fish.timer_to_birth)
+            .chain([lifecycle.newborn, lifecycle.reset])
+            .max()
+            .unwrap_or_default();
+        let timers = usize::from(highest) + 1;
+        let mut buckets = vec![BigUint::zero(); timers * lifecycle.generations()];
+        for fish in fishes {
+            buckets[usize::from(fish.timer_to_birth)] += 1u32;
+        }
+        Population {
+            lifecycle,
+            timers,
+            buckets,
+        }
+    }
+
+    fn bucket(&self, births: usize, timer: usize) -> usize {
+        births * self.timers + timer
+    }
+
+    /// One night: every timer counts down, fish at zero give birth to a newborn and reset, or
+    /// die when that was their last birth.
+    pub(crate) fn step(&mut self) {
+        let mut buckets = vec![BigUint::zero(); self.buckets.len()];
+        for births in 0..self.lifecycle.generations() {
+            for timer in 1..self.timers {
+                buckets[self.bucket(births, timer - 1)] +=
+                    &self.buckets[self.bucket(births, timer)];
+            }
+            let parents = &self.buckets[self.bucket(births, 0)];
+            buckets[self.bucket(0, usize::from(self.lifecycle.newborn))] += parents;
+            if let Some((births, timer)) = self.lifecycle.after_birth(births) {
+                buckets[self.bucket(births, timer)] += parents;
+            }
+        }
+        self.buckets = buckets;
+    }
+
+    /// Steps night by night, linear in `nights`.
+    pub(crate) fn simulate(&mut self, nights: u64) {
+        for _ in 0..nights {
+            self.step();
+        }
+    }
+
+    pub(crate) fn count(&self) -> BigUint {
+        self.buckets.iter().sum()
+    }
+
+    /// The matrix that maps the buckets of one night onto those of the next night.
+    fn transition(&self) -> Matrix {
+        let size = self.buckets.len();
+        let mut cells = vec![vec![BigUint::zero(); size]; size];
+        for births in 0..self.lifecycle.generations() {
+            for timer in 1..self.timers {
+                cells[self.bucket(births, timer - 1)][self.bucket(births, timer)] += 1u32;
+            }
+            let parents = self.bucket(births, 0);
+            cells[self.bucket(0, usize::from(self.lifecycle.newborn))][parents] += 1u32;
+            if let Some((births, timer)) = self.lifecycle.after_birth(births) {
+                cells[self.bucket(births, timer)][parents] += 1u32;
+            }
+        }
+        Matrix { cells }
+    }
+
+    /// Number of fish after `nights`, in `O(log nights)` matrix multiplications. With a
+    /// `modulus` the count is reduced by it, which keeps astronomically many nights feasible.
+    pub(crate) fn count_after(&self, nights: u64, modulus: Option<&BigUint>) -> BigUint {
+        let buckets = self
+            .transition()
+            .pow(nights, modulus)
+            .apply(&self.buckets, modulus);
+        let count: BigUint = buckets.iter().sum();
+        match modulus {
+            Some(modulus) => count % modulus,
+            None => count,
+        }
+    }
+
+    /// Writes the buckets of every night from now up to `nights` as CSV, one row per night.
+    pub(crate) fn write_csv<W: Write>(&self, nights: u64, writer: &mut W) -> io::Result<()> {
+        let mut header: Vec<String> = vec!["night".to_string()];
+        for births in 0..self.lifecycle.generations() {
+            for timer in 0..self.timers {
+                header.push(match self.lifecycle.mortality {
+                    Some(_) => format!("births_{}_timer_{}", births, timer),
+                    None => format!("timer_{}", timer),
+                });
+            }
+        }
+        writeln!(writer, "{}", header.join(","))?;
+
+        let mut population = self.clone();
+        for night in 0..=nights {
+            let row: Vec<String> = population.buckets.iter().map(|b| b.to_string()).collect();
+            writeln!(writer, "{},{}", night, row.join(","))?;
+            population.step();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::solver::{bounds, total_fuel, Crab};
+use std::io;
+use std::io::Write;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The fuel it takes to align on every position between the outermost crabs.
+pub(crate) fn curve<F>(crabs: &[Crab], cost: F) -> Vec<(i64, i64)>
+where
+    F: Fn(i64) -> i64,
+{
+    match bounds(crabs) {
+        Some((min, max)) => (min..=max)
+            .map(|position| (position, total_fuel(crabs, position, &cost)))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Writes curves over the same positions as CSV, with a fuel column per named curve.
+pub(crate) fn write_csv<W: Write>(
+    curves: &[(&str, &[(i64, i64)])],
+    writer: &mut W,
+) -> io::Result<()> {
+    let names: Vec<&str> = curves.iter().map(|(name, _)| *name).collect();
+    writeln!(writer, "position,{}", names.join(","))?;
+    let length = curves
+        .iter()
+        .map(|(_, curve)| curve.len())
+        .min()
+        .unwrap_or_default();
+    for i in 0..length {
+        let fuels: Vec<String> = curves
+            .iter()
+            .map(|(_, curve)| curve[i].1.to_string())
+            .collect();
+        writeln!(writer, "{},{}", curves[0].1[i].0, fuels.join(","))?;
+    }
+    Ok(())
+}
+
+/// Draws the curve in at most `width` characters, each the lowest fuel of the positions it
+/// covers so the minimum never falls between two characters.
+pub(crate) fn sparkline(curve: &[(i64, i64)], width: usize) -> String {
+    if curve.is_empty() || width == 0 {
+        return String::new();
+    }
+    let chunk = curve.len().div_ceil(width);
+    let fuels: Vec<i64> = curve
+        .chunks(chunk)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|(_, fuel)| *fuel)
+                .min()
+                .unwrap_or_default()
+        })
+        .collect();
+    let low = fuels.iter().min().copied().unwrap_or_default();
+    let high = fuels.iter().max().copied().unwrap_or_default();
+    fuels
+        .iter()
+        .map(|fuel| match high - low {
+            0 => BARS[0],
+            range => {
+                BARS[((fuel - low) as i128 * (BARS.len() - 1) as i128 / range as i128) as usize]
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+//! The positions of the crabs, each with an optional weight.
+
+use std::fmt::{Display, Formatter};
+
+/// Positions are at most this far from 0.
+pub const MAX_POSITION: i64 = 1 << 20;
+/// The weights of all crabs together are at most this. With the bound on the positions the
+/// total fuel stays below `2^61`, so it fits an `i64` however it is burnt.
+pub const MAX_WEIGHT: i64 = 1 << 20;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    InvalidPosition { value: String },
+    InvalidWeight { value: String },
+    TooFar { position: i64 },
+    TooHeavy { weight: i64 },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidPosition { value } => {
+                write!(f, "{:?} is not a position", value)
+            }
+            ParseError::InvalidWeight { value } => {
+                write!(f, "{:?} is not a weight", value)
+            }
+            ParseError::TooFar { position } => {
+                write!(f, "{} is more than {} from 0", position, MAX_POSITION)
+            }
+            ParseError::TooHeavy { weight } => {
+                write!(f, "A weight of {} is more than {}", weight, MAX_WEIGHT)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Either `position` or `position:weight`, as `(position, weight)`.
+pub fn parse_crab(text: &str) -> Result<(i64, i64), ParseError> {
+    let (position, weight) = match text.split_once(':') {
+        Some((position, weight)) => (position.trim(), Some(weight.trim())),
+        None => (text.trim(), None),
+    };
+    let position = position
+        .parse::<i64>()
+        .map_err(|_| ParseError::InvalidPosition {
+            value: position.to_string(),
+        })?;
+    let weight = match weight {
+        Some(weight) => weight
+            .parse::<i64>()
+            .map_err(|_| ParseError::InvalidWeight {
+                value: weight.to_string(),
+            })?,
+        None => 1,
+    };
+    if position.unsigned_abs() > MAX_POSITION as u64 {
+        return Err(ParseError::TooFar { position });
+    }
+    if weight.unsigned_abs() > MAX_WEIGHT as u64 {
+        return Err(ParseError::TooHeavy { weight });
+    }
+    Ok((position, weight))
+}
+
+/// The comma separated crabs on the first line, the lines after it are not read.
+pub fn parse_input(input: &str) -> Result<Vec<(i64, i64)>, ParseError> {
+    let crabs: Vec<(i64, i64)> = match input.lines().next().map(str::trim) {
+        Some(line) if !line.is_empty() => {
+            line.split(',').map(parse_crab).collect::<Result<_, _>>()?
+        }
+        _ => Vec::new(),
+    };
+    let weight: i64 = crabs.iter().map(|&(_, weight)| weight).sum();
+    if weight > MAX_WEIGHT {
+        return Err(ParseError::TooHeavy { weight });
+    }
+    Ok(crabs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("16,1, 2:3\r\nnot read"),
+            Ok(vec![(16, 1), (1, 1), (2, 3)])
+        );
+        assert_eq!(parse_input(""), Ok(vec![]));
+        assert_eq!(
+            parse_input("16,x"),
+            Err(ParseError::InvalidPosition {
+                value: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("16:2.5"),
+            Err(ParseError::InvalidWeight {
+                value: "2.5".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("0,5000000000"),
+            Err(ParseError::TooFar {
+                position: 5_000_000_000
+            })
+        );
+        assert_eq!(
+            parse_input("-1048576,1048576"),
+            Ok(vec![(-1 << 20, 1), (1 << 20, 1)])
+        );
+        assert_eq!(
+            parse_input("1:1048576,2"),
+            Err(ParseError::TooHeavy { weight: 1_048_577 })
+        );
+        assert_eq!(
+            parse_input("1:-9223372036854775808"),
+            Err(ParseError::TooHeavy { weight: i64::MIN })
+        );
+    }
+}
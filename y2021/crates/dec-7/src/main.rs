@@ -1,92 +1,271 @@
-use std::fs::File;
+mod landscape;
+mod solver;
+
+use dec_7::parse_input;
+use landscape::{curve, sparkline, write_csv};
+use solver::{brute_force, linear, mean, median, ternary, triangular, Alignment, Crab};
+use std::fs::{self, File};
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Method {
+    ClosedForm,
+    Ternary,
+    BruteForce,
+}
+
+/// Runs `cargo run -p dec-7 -- [--ternary | --brute-force] [--sparkline] [--csv FILE]`. The
+/// sparkline and the CSV need the fuel for every position for both ways of burning fuel, so that
+/// is only worked out when one of them is asked for.
 fn main() {
-    if let Ok(numbers) = read_input("./resources/input-dec-7") {
-        let (best_position, min_fuel) = calulation_1(numbers);
-        println!("best is: {} fuel: {}", best_position, min_fuel)
+    let mut method = Method::ClosedForm;
+    let mut show_sparkline = false;
+    let mut csv: Option<String> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ternary" => method = Method::Ternary,
+            "--brute-force" => method = Method::BruteForce,
+            "--sparkline" => show_sparkline = true,
+            "--csv" => csv = args.next(),
+            x => panic!("Unknown argument: {}", x),
+        }
     }
 
-    if let Ok(numbers) = read_input("./resources/input-dec-7") {
-        let (best_position, min_fuel) = calulation_2(numbers);
-        println!("best is: {} fuel: {}", best_position, min_fuel)
+    if let Ok(crabs) = read_input("./resources/input-dec-7") {
+        if let Some(best) = calulation_1(crabs.clone(), method) {
+            println!("best is: {:?} fuel: {}", best.positions, best.fuel);
+        }
+        if let Some(best) = calulation_2(crabs.clone(), method) {
+            println!("best is: {:?} fuel: {}", best.positions, best.fuel);
+        }
+
+        if show_sparkline || csv.is_some() {
+            let linear_curve = curve(&crabs, linear);
+            let triangular_curve = curve(&crabs, triangular);
+            if show_sparkline {
+                println!("{}", sparkline(&linear_curve, 80));
+                println!("{}", sparkline(&triangular_curve, 80));
+            }
+            if let Some(csv) = csv {
+                let mut file = File::create(csv).expect("Could not create the CSV file");
+                write_csv(
+                    &[("linear", &linear_curve), ("triangular", &triangular_curve)],
+                    &mut file,
+                )
+                .expect("Could not write the CSV file");
+            }
+        }
     }
 }
 
-fn calulation_1(numbers: Vec<i32>) -> (i32, i64) {
-    let min = *numbers.iter().min().unwrap();
-    let max = *numbers.iter().max().unwrap();
-
-    let mut min_fuel: i64 = numbers.iter().sum::<i32>() as i64;
-    let mut best_position: i32 = 0;
-    for i in min..max {
-        let mut current_fuel = 0;
-        for x in numbers.iter() {
-            current_fuel += (i - x).abs() as i64;
-        }
-        if current_fuel < min_fuel {
-            min_fuel = current_fuel;
-            best_position = i;
-        }
+fn calulation_1(crabs: Vec<Crab>, method: Method) -> Option<Alignment> {
+    match method {
+        Method::ClosedForm => median(&crabs),
+        Method::Ternary => ternary(&crabs, linear),
+        Method::BruteForce => brute_force(&crabs, linear),
     }
-    (best_position, min_fuel)
 }
 
-fn calulation_2(numbers: Vec<i32>) -> (i32, i64) {
-    let min = *numbers.iter().min().unwrap();
-    let max = *numbers.iter().max().unwrap();
-
-    let mut min_fuel: i64 = numbers.iter().map(|x| (1..=*x).sum::<i32>()).sum::<i32>() as i64;
-    let mut best_position: i32 = 0;
-    for i in min..max {
-        let mut current_fuel = 0;
-        for x in numbers.iter() {
-            let steps = (i - x).abs();
-            current_fuel += (1..=steps).sum::<i32>() as i64;
-        }
-        if current_fuel < min_fuel {
-            min_fuel = current_fuel;
-            best_position = i;
-        }
+fn calulation_2(crabs: Vec<Crab>, method: Method) -> Option<Alignment> {
+    match method {
+        Method::ClosedForm => mean(&crabs),
+        Method::Ternary => ternary(&crabs, triangular),
+        Method::BruteForce => brute_force(&crabs, triangular),
     }
-    (best_position, min_fuel)
 }
 
-fn read_input<P>(filename: P) -> io::Result<Vec<i32>>
+/// Reads the comma separated crabs on the first line, each `position` or `position:weight`.
+fn read_input<P>(filename: P) -> io::Result<Vec<Crab>>
 where
     P: AsRef<Path>,
 {
-    let mut numbers: Vec<i32> = Vec::new();
+    let input = fs::read_to_string(filename)?;
+    let crabs =
+        parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    crabs
+        .into_iter()
+        .map(Crab::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::landscape::{curve, sparkline, write_csv};
+    use crate::solver::{brute_force, linear, mean, median, ternary, triangular, Crab, CrabError};
+    use crate::{calulation_1, calulation_2, Method};
+    use aoc::differential::{inputs, Differential};
+    use aoc::y2021::crabs;
+    use rand::Rng;
+
+    fn unweighted(positions: &[i64]) -> Vec<Crab> {
+        positions
+            .iter()
+            .map(|&position| Crab::from(position))
+            .collect()
+    }
+
+    #[test]
+    fn solution_1() {
+        let numbers = unweighted(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        assert_eq!(
+            calulation_1(numbers, Method::ClosedForm).unwrap().fuel,
+            37i64
+        )
+    }
+    #[test]
+    fn solution_2() {
+        let numbers = unweighted(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        assert_eq!(
+            calulation_2(numbers, Method::ClosedForm).unwrap().fuel,
+            168i64
+        )
+    }
 
-    let file = File::open(filename)?;
-    let mut reader = BufReader::new(file);
+    #[test]
+    fn methods_agree() {
+        let numbers = unweighted(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        for method in [Method::ClosedForm, Method::Ternary, Method::BruteForce] {
+            let best = calulation_1(numbers.clone(), method).unwrap();
+            assert_eq!((best.positions, best.fuel), (2..=2, 37));
+            let best = calulation_2(numbers.clone(), method).unwrap();
+            assert_eq!((best.positions, best.fuel), (5..=5, 168));
+        }
+    }
+
+    #[test]
+    fn ties() {
+        // Every position between the two middle crabs is equally good.
+        let crabs = unweighted(&[0, 3, 10, 20]);
+        assert_eq!(median(&crabs).unwrap().positions, 3..=10);
+        assert_eq!(ternary(&crabs, |d| d).unwrap().positions, 3..=10);
+        assert_eq!(brute_force(&crabs, |d| d).unwrap().positions, 3..=10);
+
+        // The best position can be the last crab, which the old loop never tried.
+        let crabs = unweighted(&[0, 9, 9]);
+        assert_eq!(median(&crabs).unwrap().positions, 9..=9);
+
+        let crabs = unweighted(&[0, 1]);
+        assert_eq!(mean(&crabs).unwrap().positions, 0..=1);
+        assert_eq!(ternary(&crabs, triangular).unwrap().positions, 0..=1);
 
-    let mut numbers_input: String = String::new();
-    if reader.read_line(&mut numbers_input).is_ok() {
-        numbers = numbers_input
-            .trim()
+        // Any convex cost works with the ternary search.
+        let crabs = unweighted(&[1, 5, 6, 30]);
+        assert_eq!(ternary(&crabs, |d| d * d), brute_force(&crabs, |d| d * d));
+    }
+
+    #[test]
+    fn weights() {
+        let weighted: Vec<Crab> = "0:3, 10, 4:1"
             .split(',')
-            .map(|x| x.parse::<i32>().unwrap())
+            .map(|c| c.parse().unwrap())
             .collect();
+        assert_eq!(
+            weighted[0],
+            Crab {
+                position: 0,
+                weight: 3
+            }
+        );
+        let expanded = unweighted(&[0, 0, 0, 10, 4]);
+        for (cost, expected) in [(linear as fn(i64) -> i64, 0..=0), (triangular, 3..=3)] {
+            let best = brute_force(&weighted, cost).unwrap();
+            assert_eq!(best, brute_force(&expanded, cost).unwrap());
+            assert_eq!(best, ternary(&weighted, cost).unwrap());
+            assert_eq!(best.positions, expected);
+        }
+        assert_eq!(median(&weighted), brute_force(&weighted, linear));
+        assert_eq!(mean(&weighted), brute_force(&weighted, triangular));
+
+        // Half of the weight on either side makes everything in between equally good.
+        let balanced: Vec<Crab> = "2:2,5,9".split(',').map(|c| c.parse().unwrap()).collect();
+        assert_eq!(median(&balanced).unwrap().positions, 2..=5);
+        assert_eq!(brute_force(&balanced, linear).unwrap().positions, 2..=5);
     }
 
-    Ok(numbers)
-}
+    #[test]
+    fn non_positive_weights() {
+        assert_eq!("3:-2".parse::<Crab>(), Err(CrabError::Weight(-2)));
+        assert_eq!("3:0".parse::<Crab>(), Err(CrabError::Weight(0)));
+        assert!(matches!("3:x".parse::<Crab>(), Err(CrabError::Parse(_))));
+    }
 
-#[cfg(test)]
-mod test {
-    use crate::{calulation_1, calulation_2};
+    #[test]
+    fn far_and_heavy() {
+        // The largest distance and total weight the parser lets through, with half of the
+        // weight on either side of 0.
+        let crabs: Vec<Crab> = dec_7::parse_input("-1048576:524287,1048576:524288,0")
+            .unwrap()
+            .into_iter()
+            .map(|crab| Crab::try_from(crab).unwrap())
+            .collect();
+        let best = calulation_1(crabs.clone(), Method::ClosedForm).unwrap();
+        assert_eq!(best.positions, 0..=1_048_576);
+        assert_eq!(best.fuel, ((1 << 20) - 1) << 20);
+        let best_2 = calulation_2(crabs.clone(), Method::ClosedForm).unwrap();
+        for method in [Method::Ternary, Method::BruteForce] {
+            assert_eq!(calulation_1(crabs.clone(), method), Some(best.clone()));
+            assert_eq!(calulation_2(crabs.clone(), method), Some(best_2.clone()));
+        }
+    }
 
     #[test]
-    fn solution_1() {
-        let numbers = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        assert_eq!(calulation_1(numbers).1, 37i64)
+    fn landscape() {
+        let numbers = unweighted(&[0, 2, 4]);
+        let linear_curve = curve(&numbers, linear);
+        assert_eq!(linear_curve, vec![(0, 6), (1, 5), (2, 4), (3, 5), (4, 6)]);
+        assert_eq!(sparkline(&linear_curve, 10), "█▄▁▄█");
+        assert_eq!(sparkline(&linear_curve, 3), "▄▁█");
+
+        let triangular_curve = curve(&numbers, triangular);
+        let mut csv: Vec<u8> = Vec::new();
+        write_csv(
+            &[("linear", &linear_curve), ("triangular", &triangular_curve)],
+            &mut csv,
+        )
+        .unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().next(), Some("position,linear,triangular"));
+        assert_eq!(csv.lines().nth(3), Some("2,4,6"));
     }
+
     #[test]
-    fn solution_2() {
-        let numbers = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        assert_eq!(calulation_2(numbers).1, 168i64)
+    fn differential() {
+        // Positions with weights, as the shrinker only knows numbers and pairs
+        let weighted = |crabs: &[(i64, i64)]| -> Vec<Crab> {
+            crabs
+                .iter()
+                .map(|&(position, weight)| Crab { position, weight })
+                .collect()
+        };
+        let valid = |crabs: &Vec<(i64, i64)>| crabs.iter().all(|&(_, weight)| weight > 0);
+        let generated = || {
+            inputs(100, 7, |rng| {
+                crabs(rng, 30, 200)
+                    .trim()
+                    .split(',')
+                    .map(|position| (position.parse().unwrap(), rng.gen_range(1..=5)))
+                    .collect()
+            })
+        };
+        for (method, name) in [
+            (Method::Ternary, "ternary"),
+            (Method::BruteForce, "brute force"),
+        ] {
+            Differential::new("median", |crabs: &Vec<(i64, i64)>| {
+                calulation_1(weighted(crabs), Method::ClosedForm)
+            })
+            .register(name, move |crabs| calulation_1(weighted(crabs), method))
+            .only(valid)
+            .assert(generated());
+            Differential::new("mean", |crabs: &Vec<(i64, i64)>| {
+                calulation_2(weighted(crabs), Method::ClosedForm)
+            })
+            .register(name, move |crabs| calulation_2(weighted(crabs), method))
+            .only(valid)
+            .assert(generated());
+        }
     }
 }
@@ -0,0 +1,235 @@
+use dec_7::{parse_crab, ParseError};
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// A crab at `position` that burns `weight` times the fuel of an ordinary crab.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct Crab {
+    pub(crate) position: i64,
+    pub(crate) weight: i64,
+}
+
+impl From<i64> for Crab {
+    fn from(position: i64) -> Self {
+        Crab {
+            position,
+            weight: 1,
+        }
+    }
+}
+
+/// Either `position` or `position:weight`.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum CrabError {
+    Parse(ParseError),
+    /// Without a positive weight the fuel stops being convex, which the solvers rely on.
+    Weight(i64),
+}
+
+impl Display for CrabError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrabError::Parse(error) => write!(f, "{}", error),
+            CrabError::Weight(weight) => write!(f, "Weight {} is not positive", weight),
+        }
+    }
+}
+
+impl std::error::Error for CrabError {}
+
+impl From<ParseError> for CrabError {
+    fn from(error: ParseError) -> Self {
+        CrabError::Parse(error)
+    }
+}
+
+impl TryFrom<(i64, i64)> for Crab {
+    type Error = CrabError;
+
+    fn try_from((position, weight): (i64, i64)) -> Result<Self, Self::Error> {
+        if weight <= 0 {
+            return Err(CrabError::Weight(weight));
+        }
+        Ok(Crab { position, weight })
+    }
+}
+
+impl FromStr for Crab {
+    type Err = CrabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Crab::try_from(parse_crab(s)?)
+    }
+}
+
+/// Fuel for moving one step per step taken.
+pub(crate) fn linear(distance: i64) -> i64 {
+    distance
+}
+
+/// Fuel when every next step costs one more than the previous one.
+pub(crate) fn triangular(distance: i64) -> i64 {
+    distance * (distance + 1) / 2
+}
+
+/// The cheapest positions to align on and the fuel that takes. The positions are a range as
+/// the minima of a convex function are always next to each other.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Alignment {
+    pub(crate) positions: RangeInclusive<i64>,
+    pub(crate) fuel: i64,
+}
+
+pub(crate) fn total_fuel<F>(crabs: &[Crab], position: i64, cost: &F) -> i64
+where
+    F: Fn(i64) -> i64,
+{
+    crabs
+        .iter()
+        .map(|crab| crab.weight * cost((crab.position - position).abs()))
+        .sum()
+}
+
+pub(crate) fn bounds(crabs: &[Crab]) -> Option<(i64, i64)> {
+    let positions = crabs.iter().map(|crab| crab.position);
+    Some((positions.clone().min()?, positions.max()?))
+}
+
+/// Compares every position in `candidates`, which has to contain all the best positions.
+fn best_of<F>(crabs: &[Crab], candidates: RangeInclusive<i64>, cost: &F) -> Option<Alignment>
+where
+    F: Fn(i64) -> i64,
+{
+    let fuels: Vec<(i64, i64)> = candidates
+        .map(|position| (position, total_fuel(crabs, position, cost)))
+        .collect();
+    let fuel = fuels.iter().map(|(_, fuel)| *fuel).min()?;
+    let best: Vec<i64> = fuels
+        .iter()
+        .filter(|(_, f)| *f == fuel)
+        .map(|(p, _)| *p)
+        .collect();
+    Some(Alignment {
+        positions: best[0]..=best[best.len() - 1],
+        fuel,
+    })
+}
+
+/// Tries every position between the outermost crabs.
+pub(crate) fn brute_force<F>(crabs: &[Crab], cost: F) -> Option<Alignment>
+where
+    F: Fn(i64) -> i64,
+{
+    let (min, max) = bounds(crabs)?;
+    best_of(crabs, min..=max, &cost)
+}
+
+/// Widens the single best `position` to every position that takes the same `fuel`. Left of
+/// the minima the fuel only goes down and right of them only up, so both ends are found by
+/// binary search.
+fn ties<F>(crabs: &[Crab], position: i64, fuel: i64, cost: &F) -> RangeInclusive<i64>
+where
+    F: Fn(i64) -> i64,
+{
+    let (min, max) = bounds(crabs).unwrap_or((position, position));
+    let is_best = |p: i64| total_fuel(crabs, p, cost) == fuel;
+
+    let (mut low, mut high) = (min, position);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if is_best(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    let first = low;
+
+    let (mut low, mut high) = (position, max);
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+        if is_best(middle) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    first..=low
+}
+
+/// Ternary search for any convex `cost`, in `O(n log range)`.
+pub(crate) fn ternary<F>(crabs: &[Crab], cost: F) -> Option<Alignment>
+where
+    F: Fn(i64) -> i64,
+{
+    let (mut low, mut high) = bounds(crabs)?;
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (left, right) = (low + third, high - third);
+        let (left_fuel, right_fuel) = (
+            total_fuel(crabs, left, &cost),
+            total_fuel(crabs, right, &cost),
+        );
+        match left_fuel.cmp(&right_fuel) {
+            std::cmp::Ordering::Less => high = right - 1,
+            std::cmp::Ordering::Greater => low = left + 1,
+            std::cmp::Ordering::Equal => (low, high) = (left, right),
+        }
+    }
+    let (position, fuel) = (low..=high)
+        .map(|position| (position, total_fuel(crabs, position, &cost)))
+        .min_by_key(|(_, fuel)| *fuel)?;
+    Some(Alignment {
+        positions: ties(crabs, position, fuel, &cost),
+        fuel,
+    })
+}
+
+/// With linear fuel the weighted median is best: the position with at most half of the weight
+/// on either side. If the crabs up to one of them weigh exactly half, every position up to the
+/// next crab is just as good.
+pub(crate) fn median(crabs: &[Crab]) -> Option<Alignment> {
+    let mut sorted: Vec<Crab> = crabs
+        .iter()
+        .filter(|crab| crab.weight > 0)
+        .copied()
+        .collect();
+    sorted.sort_unstable_by_key(|crab| crab.position);
+    let total: i64 = sorted.iter().map(|crab| crab.weight).sum();
+
+    let mut weight = 0;
+    let middle = sorted.iter().position(|crab| {
+        weight += crab.weight;
+        2 * weight >= total
+    })?;
+    let first = sorted[middle].position;
+    let positions = match sorted.get(middle + 1) {
+        Some(next) if 2 * weight == total => first..=next.position,
+        _ => first..=first,
+    };
+    Some(Alignment {
+        fuel: total_fuel(crabs, first, &linear),
+        positions,
+    })
+}
+
+/// With triangular fuel the best position is within half a step of the weighted mean, so only
+/// the positions around it need to be compared.
+pub(crate) fn mean(crabs: &[Crab]) -> Option<Alignment> {
+    let (min, max) = bounds(crabs)?;
+    let weight: i64 = crabs.iter().map(|crab| crab.weight).sum();
+    if weight <= 0 {
+        return best_of(crabs, min..=max, &triangular);
+    }
+    let mean = crabs
+        .iter()
+        .map(|crab| crab.weight * crab.position)
+        .sum::<i64>()
+        .div_euclid(weight);
+    best_of(
+        crabs,
+        (mean - 1).max(min)..=(mean + 2).min(max),
+        &triangular,
+    )
+}
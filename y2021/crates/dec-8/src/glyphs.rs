@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Segments are named `a`, `b`, ... so there can't be more than there are letters.
+pub(crate) const MAX_SEGMENTS: usize = 26;
+
+/// A symbol and the segments that are lit to show it, segment `a` being the lowest bit.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct Glyph {
+    pub(crate) symbol: char,
+    pub(crate) segments: u32,
+}
+
+/// The symbols a display can show. The value of a symbol is its position in the table, which
+/// makes the table the digits of a number system.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Glyphs {
+    segments: usize,
+    glyphs: Vec<Glyph>,
+    lookup: HashMap<u32, usize>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum GlyphError {
+    InvalidLine { line: usize },
+    TooManySegments { segments: usize },
+    SameSegments { first: char, second: char },
+    Empty,
+}
+
+impl Display for GlyphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlyphError::InvalidLine { line } => {
+                write!(f, "Line {}: expected a symbol and its segments", line)
+            }
+            GlyphError::TooManySegments { segments } => write!(
+                f,
+                "{} segments, at most {} are supported",
+                segments, MAX_SEGMENTS
+            ),
+            GlyphError::SameSegments { first, second } => {
+                write!(f, "{} and {} light the same segments", first, second)
+            }
+            GlyphError::Empty => write!(f, "No glyphs"),
+        }
+    }
+}
+
+impl std::error::Error for GlyphError {}
+
+/// Reads a pattern like `"cfbegad"` as a bitmask, `a` being the lowest bit.
+pub(crate) fn pattern(s: &str, segments: usize) -> Option<u32> {
+    s.chars().try_fold(0u32, |mask, c| match c {
+        'a'..='z' if ((c as u8 - b'a') as usize) < segments => Some(mask | 1 << (c as u8 - b'a')),
+        _ => None,
+    })
+}
+
+impl Glyphs {
+    /// Builds the table from symbols and the letters of their segments.
+    pub(crate) fn new(segments: usize, glyphs: &[(char, &str)]) -> Result<Glyphs, GlyphError> {
+        if segments > MAX_SEGMENTS {
+            return Err(GlyphError::TooManySegments { segments });
+        }
+        if glyphs.is_empty() {
+            return Err(GlyphError::Empty);
+        }
+        let mut table = Glyphs {
+            segments,
+            glyphs: Vec::new(),
+            lookup: HashMap::new(),
+        };
+        for (line, &(symbol, lit)) in glyphs.iter().enumerate() {
+            let segments =
+                pattern(lit, segments).ok_or(GlyphError::InvalidLine { line: line + 1 })?;
+            if let Some(&other) = table.lookup.get(&segments) {
+                return Err(GlyphError::SameSegments {
+                    first: table.glyphs[other].symbol,
+                    second: symbol,
+                });
+            }
+            table.lookup.insert(segments, table.glyphs.len());
+            table.glyphs.push(Glyph { symbol, segments });
+        }
+        Ok(table)
+    }
+
+    /// The digits 0 to 9 on a seven-segment display:
+    ///
+    /// ```text
+    ///  aaaa
+    /// b    c
+    /// b    c
+    ///  dddd
+    /// e    f
+    /// e    f
+    ///  gggg
+    /// ```
+    pub(crate) fn digits() -> Glyphs {
+        Glyphs::new(7, &DIGITS).unwrap()
+    }
+
+    /// The digits followed by `A b C d E F`.
+    pub(crate) fn hexadecimal() -> Glyphs {
+        let glyphs: Vec<(char, &str)> = DIGITS.iter().chain(HEX.iter()).copied().collect();
+        Glyphs::new(7, &glyphs).unwrap()
+    }
+
+    pub(crate) fn segments(&self) -> usize {
+        self.segments
+    }
+
+    pub(crate) fn all(&self) -> u32 {
+        ((1u64 << self.segments) - 1) as u32
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Glyph> {
+        self.glyphs.iter()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub(crate) fn pattern(&self, s: &str) -> Option<u32> {
+        pattern(s, self.segments)
+    }
+
+    /// The value of the glyph with exactly these segments lit.
+    pub(crate) fn value(&self, segments: u32) -> Option<usize> {
+        self.lookup.get(&segments).copied()
+    }
+
+    pub(crate) fn symbol(&self, value: usize) -> Option<char> {
+        self.glyphs.get(value).map(|glyph| glyph.symbol)
+    }
+
+    /// Whether a pattern of `length` wires can only be one glyph, whatever the wiring.
+    pub(crate) fn unique_length(&self, length: usize) -> bool {
+        self.glyphs
+            .iter()
+            .filter(|glyph| glyph.segments.count_ones() as usize == length)
+            .count()
+            == 1
+    }
+}
+
+/// One glyph per line: its symbol, whitespace and the letters of its segments. The number of
+/// segments is that of the highest letter used. Blank lines and lines starting with `#` are
+/// skipped.
+impl FromStr for Glyphs {
+    type Err = GlyphError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut glyphs: Vec<(char, &str)> = Vec::new();
+        for (line, text) in s.lines().enumerate() {
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let mut parts = text.split_whitespace();
+            let glyph = match (parts.next(), parts.next(), parts.next()) {
+                (Some(symbol), Some(lit), None) if symbol.chars().count() == 1 => {
+                    symbol.chars().next().map(|symbol| (symbol, lit))
+                }
+                _ => None,
+            };
+            glyphs.push(glyph.ok_or(GlyphError::InvalidLine { line: line + 1 })?);
+        }
+        let segments = glyphs
+            .iter()
+            .flat_map(|(_, lit)| lit.chars())
+            .map(|c| (c as usize).saturating_sub('a' as usize) + 1)
+            .max()
+            .unwrap_or_default();
+        Glyphs::new(segments, &glyphs)
+    }
+}
+
+const DIGITS: [(char, &str); 10] = [
+    ('0', "abcefg"),
+    ('1', "cf"),
+    ('2', "acdeg"),
+    ('3', "acdfg"),
+    ('4', "bcdf"),
+    ('5', "abdfg"),
+    ('6', "abdefg"),
+    ('7', "acf"),
+    ('8', "abcdefg"),
+    ('9', "abcdfg"),
+];
+
+const HEX: [(char, &str); 6] = [
+    ('A', "abcdef"),
+    ('b', "bdefg"),
+    ('C', "abeg"),
+    ('d', "cdefg"),
+    ('E', "abdeg"),
+    ('F', "abde"),
+];
@@ -0,0 +1,95 @@
+//! The notes on the seven-segment displays, split into words.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    MissingSeparator {
+        line: usize,
+    },
+    ExtraSeparator {
+        line: usize,
+    },
+    /// Patterns are written with the letters of the wires.
+    InvalidPattern {
+        line: usize,
+        pattern: String,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingSeparator { line } => {
+                write!(f, "Line {}: no `|` before the shown patterns", line)
+            }
+            ParseError::ExtraSeparator { line } => {
+                write!(f, "Line {}: more than one `|`", line)
+            }
+            ParseError::InvalidPattern { line, pattern } => {
+                write!(f, "Line {}: {:?} is not a pattern of wires", line, pattern)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Every non-blank line split at whitespace, the `|` kept as a word of its own. Which letters
+/// are wires depends on the glyphs, so any lowercase letter passes here.
+pub fn parse_input(input: &str) -> Result<Vec<Vec<String>>, ParseError> {
+    let mut displays: Vec<Vec<String>> = Vec::new();
+    for (line, text) in input.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if text.is_empty() {
+            continue;
+        }
+        let display: Vec<String> = text.split_whitespace().map(|x| x.to_string()).collect();
+        for word in display.iter().filter(|x| x.as_str() != "|") {
+            if !word.chars().all(|c| c.is_ascii_lowercase()) {
+                return Err(ParseError::InvalidPattern {
+                    line,
+                    pattern: word.to_string(),
+                });
+            }
+        }
+        match display.iter().filter(|x| x.as_str() == "|").count() {
+            0 => return Err(ParseError::MissingSeparator { line }),
+            1 => displays.push(display),
+            _ => return Err(ParseError::ExtraSeparator { line }),
+        }
+    }
+    Ok(displays)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_input, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("ab cfg |  ba\r\n\n"),
+            Ok(vec![vec![
+                "ab".to_string(),
+                "cfg".to_string(),
+                "|".to_string(),
+                "ba".to_string()
+            ]])
+        );
+        assert_eq!(
+            parse_input("ab | ba\nab ba"),
+            Err(ParseError::MissingSeparator { line: 2 })
+        );
+        assert_eq!(
+            parse_input("ab | ba | ab"),
+            Err(ParseError::ExtraSeparator { line: 1 })
+        );
+        assert_eq!(
+            parse_input("ab | b|a"),
+            Err(ParseError::InvalidPattern {
+                line: 1,
+                pattern: "b|a".to_string()
+            })
+        );
+    }
+}
@@ -1,308 +1,372 @@
-use regex::Regex;
-use std::fmt::Formatter;
-use std::fs::File;
+mod glyphs;
+mod wiring;
+
+use dec_8::parse_input;
+use glyphs::Glyphs;
+use std::fs;
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
+use wiring::{deduce, Deduction};
 
-#[derive(Debug)]
-struct Number {
-    value: i32,
-    chars: String,
-}
-
-impl Clone for Number {
-    fn clone(&self) -> Self {
-        Number {
-            value: self.value,
-            chars: self.chars.clone(),
+/// Runs `cargo run -p dec-8 -- [--hex | --glyphs FILE]` to read the displays with other glyphs
+/// than the digits, see `Glyphs::from_str` for the file format.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut custom = true;
+    let glyphs = match args.next().as_deref() {
+        Some("--hex") => Glyphs::hexadecimal(),
+        Some("--glyphs") => {
+            let file = args.next().expect("--glyphs needs a file");
+            let glyphs = std::fs::read_to_string(file).expect("Could not read the glyphs");
+            glyphs.parse::<Glyphs>().unwrap_or_else(|e| panic!("{}", e))
         }
-    }
-}
-
-impl Number {
-    fn new(value: i32, chars: &str) -> Number {
-        Number {
-            value,
-            chars: chars.to_string(),
+        Some(x) => panic!("Unknown argument: {}", x),
+        None => {
+            custom = false;
+            Glyphs::digits()
         }
-    }
+    };
 
-    fn to_regex(&self) -> Regex {
-        Regex::new(format!("[{}]", self.chars).as_str()).unwrap()
-    }
-
-    fn equal(&self, other: &str) -> bool {
-        self.to_regex().find_iter(other).count().eq(&other.len()) && self.chars.len() == other.len()
-    }
-}
+    if let Ok(displays) = read_input("./resources/input-dec-8") {
+        println!(
+            "Glyphs with a unique length appear {} times.",
+            count_unique_lengths(&displays, &glyphs)
+        );
 
-impl std::fmt::Display for Number {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.chars)
+        for (line, reading) in read_displays(&displays, &glyphs).iter().enumerate() {
+            match reading {
+                Reading::Number { text, .. } if custom => {
+                    println!("Display {}: {}", line + 1, text)
+                }
+                Reading::Number { .. } => {}
+                Reading::Invalid => println!("Display {}: not a valid pattern", line + 1),
+                Reading::NotANumber => println!("Display {}: output is not a number", line + 1),
+                Reading::Ambiguous => {
+                    println!("Display {}: more than one wiring fits", line + 1)
+                }
+                Reading::Impossible => println!("Display {}: no wiring fits", line + 1),
+            }
+        }
+        println!(
+            "Sum of all displays: {}",
+            sum_of_displays(&displays, &glyphs)
+        );
     }
 }
 
-#[derive(Debug)]
-struct Display {
-    zero: Option<Number>,
-    one: Option<Number>,
-    two: Option<Number>,
-    three: Option<Number>,
-    four: Option<Number>,
-    five: Option<Number>,
-    six: Option<Number>,
-    seven: Option<Number>,
-    eight: Option<Number>,
-    nine: Option<Number>,
+/// What a line of the notes shows on its display.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Reading {
+    Number {
+        value: u64,
+        text: String,
+    },
+    /// A pattern uses a wire the display doesn't have, or the `|` is missing.
+    Invalid,
+    /// The wiring is known but the output isn't made of glyphs.
+    NotANumber,
+    Ambiguous,
+    Impossible,
 }
 
-trait Analyzer {
-    type Value;
-    fn equals(&self, s: &str) -> bool;
-    fn count_same_chars(&self, s: &str) -> i32;
+/// Splits a line in all of its patterns and the ones shown on the display, after the `|`.
+fn entry(display: &[String], glyphs: &Glyphs) -> Option<(Vec<u32>, Vec<u32>)> {
+    let split = display.iter().position(|x| x == "|")?;
+    let patterns: Vec<u32> = display
+        .iter()
+        .filter(|x| x.as_str() != "|")
+        .map(|x| glyphs.pattern(x))
+        .collect::<Option<Vec<u32>>>()?;
+    let outputs: Vec<u32> = display[split + 1..]
+        .iter()
+        .map(|x| glyphs.pattern(x))
+        .collect::<Option<Vec<u32>>>()?;
+    Some((patterns, outputs))
 }
 
-impl Analyzer for Option<Number> {
-    type Value = i32;
-
-    fn equals(&self, other: &str) -> bool {
-        self.as_ref().unwrap().equal(other)
-    }
-
-    fn count_same_chars(&self, number: &str) -> i32 {
-        self.as_ref().unwrap().to_regex().find_iter(number).count() as i32
-    }
-}
-
-impl Display {
-    fn new() -> Display {
-        Display {
-            zero: None,
-            one: None,
-            two: None,
-            three: None,
-            four: None,
-            five: None,
-            six: None,
-            seven: None,
-            eight: None,
-            nine: None,
-        }
-    }
-
-    fn analyze_number(&mut self, number: &str) {
-        match number {
-            x if x.len() == 2 => {
-                self.one = Some(Number::new(1, x));
-            }
-            x if x.len() == 3 => {
-                self.seven = Some(Number::new(7, x));
+fn read_display(display: &[String], glyphs: &Glyphs) -> Reading {
+    // Every pattern on the line, the shown ones included, tells something about the wiring
+    let Some((patterns, outputs)) = entry(display, glyphs) else {
+        return Reading::Invalid;
+    };
+    match deduce(glyphs, &patterns) {
+        Deduction::Unique(wiring) => {
+            match (
+                wiring.number(glyphs, &outputs),
+                wiring.text(glyphs, &outputs),
+            ) {
+                (Some(value), Some(text)) => Reading::Number { value, text },
+                _ => Reading::NotANumber,
             }
-            x if x.len() == 4 => {
-                self.four = Some(Number::new(4, x));
-            }
-            x if x.len() == 7 => {
-                self.eight = Some(Number::new(8, x));
-            }
-            x => match x {
-                x if x.len() == 5 => match x {
-                    x if self.seven.count_same_chars(x) == 3 => {
-                        //7
-                        self.three = Some(Number::new(3, x));
-                    }
-                    x if self.four.count_same_chars(x) == 2 => {
-                        //4
-                        self.two = Some(Number::new(2, x));
-                    }
-                    x => {
-                        self.five = Some(Number::new(5, x));
-                    }
-                },
-                x if x.len() == 6 => match x {
-                    x if self.five.count_same_chars(x) == 5
-                        && self.one.count_same_chars(x) == 1 =>
-                    {
-                        self.six = Some(Number::new(6, x));
-                    }
-                    x if self.five.count_same_chars(x) == 5 => {
-                        self.nine = Some(Number::new(9, x));
-                    }
-                    x => {
-                        self.zero = Some(Number::new(0, x));
-                    }
-                },
-                _ => panic!("Can't parse the number!"),
-            },
-        };
-    }
-
-    fn determine_number(&self, str: &str) -> i32 {
-        match str {
-            str if self.zero.equals(str) => 0,
-            str if self.one.equals(str) => 1,
-            str if self.two.equals(str) => 2,
-            str if self.three.equals(str) => 3,
-            str if self.four.equals(str) => 4,
-            str if self.five.equals(str) => 5,
-            str if self.six.equals(str) => 6,
-            str if self.seven.equals(str) => 7,
-            str if self.eight.equals(str) => 8,
-            str if self.nine.equals(str) => 9,
-            _ => panic!("Could not determine number!"),
         }
+        Deduction::Ambiguous => Reading::Ambiguous,
+        Deduction::Impossible => Reading::Impossible,
     }
 }
 
-fn main() {
-    if let Ok(mut displays) = read_input("./resources/input-dec-8") {
-        let times = displays
-            .clone()
-            .into_iter()
-            .flatten()
-            .filter(|x| x.len() == 2 || x.len() == 3 || x.len() == 4 || x.len() == 7)
-            .count();
-        println!("Digits 1, 4, 7, or 8 appear {} times.", times);
-
-        let mut numbers: Vec<i32> = Vec::new();
-
-        for d in displays.iter_mut() {
-            let mut display = Display::new();
-
-            // Analyze the numbers
-            d[..10].sort_by(|x, y| x.len().cmp(&y.len()));
-            for n in d[..10].iter() {
-                display.analyze_number(n);
-            }
-
-            // Get the numbers shown on display
-            let mut number: Vec<i32> = Vec::new();
-            for n in d[11..].iter() {
-                let i = display.determine_number(n);
-                number.push(i);
-            }
+fn read_displays(displays: &[Vec<String>], glyphs: &Glyphs) -> Vec<Reading> {
+    displays
+        .iter()
+        .map(|display| read_display(display, glyphs))
+        .collect()
+}
 
-            // Combine the separate digits to one value
-            let number = number.iter().fold(0, |acc, elem| acc * 10 + elem);
+/// Part 1: how often a glyph that can be recognised by its length alone is shown.
+fn count_unique_lengths(displays: &[Vec<String>], glyphs: &Glyphs) -> usize {
+    displays
+        .iter()
+        .flat_map(|display| display.iter().skip_while(|x| x.as_str() != "|").skip(1))
+        .filter(|x| glyphs.unique_length(x.len()))
+        .count()
+}
 
-            numbers.push(number);
-        }
-        println!("Sum of all displays: {}", numbers.iter().sum::<i32>());
-    }
+/// Part 2: the sum of every display that could be read.
+fn sum_of_displays(displays: &[Vec<String>], glyphs: &Glyphs) -> u64 {
+    read_displays(displays, glyphs)
+        .iter()
+        .map(|reading| match reading {
+            Reading::Number { value, .. } => *value,
+            _ => 0,
+        })
+        .sum()
 }
 
 fn read_input<P>(filename: P) -> io::Result<Vec<Vec<String>>>
 where
     P: AsRef<Path>,
 {
-    let mut displays: Vec<Vec<String>> = Vec::new();
-
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
-
-    reader.lines().for_each(|line| {
-        if let Ok(line) = line {
-            let display: Vec<String> = line
-                .trim()
-                .split_whitespace()
-                .map(|x| x.to_string())
-                .collect();
-            displays.push(display);
-        }
-    });
-    Ok(displays)
+    let input = fs::read_to_string(filename)?;
+    parse_input(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{read_input, Display, Number};
-    use regex::Regex;
+    use crate::glyphs::{GlyphError, Glyphs};
+    use crate::wiring::{brute_force, deduce, wirings, Deduction};
+    use crate::{count_unique_lengths, read_displays, read_input, sum_of_displays, Reading};
+    use aoc::differential::{inputs, Differential};
+    use aoc::y2021::displays;
+    use proptest::prelude::*;
+    use rand::Rng;
+
+    fn patterns(glyphs: &Glyphs, line: &str) -> Vec<u32> {
+        line.split_whitespace()
+            .filter_map(|x| glyphs.pattern(x))
+            .collect()
+    }
+
+    /// Connects the wires of `glyphs` in a scrambled order and returns the patterns of all
+    /// glyphs as they would be seen.
+    fn scramble(glyphs: &Glyphs, shift: usize) -> Vec<u32> {
+        let segments = glyphs.segments();
+        glyphs
+            .iter()
+            .map(|glyph| {
+                (0..segments)
+                    .filter(|segment| glyph.segments & 1 << segment != 0)
+                    .fold(0, |mask, segment| {
+                        mask | 1 << ((segment * 5 + shift) % segments)
+                    })
+            })
+            .collect()
+    }
 
     #[test]
-    fn solution_2() {
-        if let Ok(mut displays) = read_input("./resources/test-input-dec-8") {
-            for d in displays.iter_mut() {
-                let mut display = Display::new();
-                d[..10].sort_by(|x, y| x.len().cmp(&y.len()));
-                for n in d[..10].iter() {
-                    display.analyze_number(n);
-                }
-                println!("{:?}", display);
+    fn solution_1() {
+        let displays = read_input("../../resources/test-input-dec-8").unwrap();
+        assert_eq!(count_unique_lengths(&displays, &Glyphs::digits()), 26);
+    }
 
-                d[..10].sort_by(|x, y| x.len().cmp(&y.len()));
-                for n in d[11..].iter() {
-                    let i = display.determine_number(n);
-                    println!("{}", i);
-                }
+    #[test]
+    fn solution_2() {
+        let displays = read_input("../../resources/test-input-dec-8").unwrap();
+        let glyphs = Glyphs::digits();
+        assert_eq!(sum_of_displays(&displays, &glyphs), 61229);
+        assert_eq!(
+            read_displays(&displays, &glyphs)[0],
+            Reading::Number {
+                value: 8394,
+                text: "8394".to_string()
             }
-        }
+        );
     }
 
     #[test]
-    fn regex_count() {
-        let test = Display {
-            zero: Some(Number {
-                value: 0,
-                chars: "abcd".to_string(),
-            }),
-            one: None,
-            two: None,
-            three: None,
-            four: None,
-            five: None,
-            six: None,
-            seven: None,
-            eight: None,
-            nine: None,
+    fn test_number_determination() {
+        let glyphs = Glyphs::digits();
+        let line = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let Deduction::Unique(wiring) = deduce(&glyphs, &patterns(&glyphs, line)) else {
+            panic!("Wiring should be unique");
         };
 
         assert_eq!(
-            test.zero.unwrap().to_regex().find_iter("efcdabgh").count(),
-            4
+            wiring.read(&glyphs, glyphs.pattern("dcbeag").unwrap()),
+            Some(0)
+        );
+        assert_eq!(wiring.read(&glyphs, glyphs.pattern("ba").unwrap()), Some(1));
+        let output = patterns(&glyphs, "cdfeb fcadb cdfeb cdbaf");
+        assert_eq!(wiring.number(&glyphs, &output), Some(5353));
+        // Twenty fives are too many for a u64, nineteen are not
+        let fives = vec![glyphs.pattern("cdfeb").unwrap(); 20];
+        assert_eq!(wiring.number(&glyphs, &fives), None);
+        assert_eq!(
+            wiring.number(&glyphs, &fives[1..]),
+            Some(5_555_555_555_555_555_555)
         );
+        assert_eq!(wiring.read(&glyphs, glyphs.pattern("abcg").unwrap()), None);
     }
 
     #[test]
-    fn create_regex() {
-        let zero = Number {
-            value: 0,
-            chars: "abcd".to_string(),
-        };
+    fn ambiguity() {
+        let glyphs = Glyphs::digits();
+        // Only knowing where the 1 is leaves its two wires interchangeable, among others.
+        let one = patterns(&glyphs, "ab");
+        assert_eq!(deduce(&glyphs, &one), Deduction::Ambiguous);
+        assert_eq!(wirings(&glyphs, &one).len(), 2 * 120);
+        // The 1, 4 and 7 are not enough to tell which of the two leftover wires is which.
+        assert!(matches!(
+            deduce(&glyphs, &patterns(&glyphs, "ab eafb dab")),
+            Deduction::Ambiguous
+        ));
+        // Two patterns of two wires can't both be a 1.
+        assert_eq!(
+            deduce(&glyphs, &patterns(&glyphs, "ab cd")),
+            Deduction::Impossible
+        );
+        assert_eq!(glyphs.pattern("abz"), None);
+        assert_eq!(glyphs.pattern("abh"), None);
+    }
 
-        assert_eq!(zero.to_regex().find_iter("efcdabgh").count(), 4);
+    #[test]
+    fn unique_lengths() {
+        let digits = Glyphs::digits();
+        let unique: Vec<usize> = (0..=7).filter(|&l| digits.unique_length(l)).collect();
+        assert_eq!(unique, vec![2, 3, 4, 7]);
+
+        // The F has as many segments as the 4.
+        let hex = Glyphs::hexadecimal();
+        let unique: Vec<usize> = (0..=7).filter(|&l| hex.unique_length(l)).collect();
+        assert_eq!(unique, vec![2, 3, 7]);
     }
 
     #[test]
-    fn test_regex() {
-        let regex = Regex::new(r"([abcd])").unwrap();
-        assert_eq!(regex.find_iter("efcdabgh").count(), 4)
+    fn hexadecimal() {
+        let glyphs = Glyphs::hexadecimal();
+        let seen = scramble(&glyphs, 3);
+        let Deduction::Unique(wiring) = deduce(&glyphs, &seen) else {
+            panic!("Wiring should be unique");
+        };
+        let output = [seen[0xF], seen[0], seen[0], seen[0xD]];
+        assert_eq!(wiring.number(&glyphs, &output), Some(0xF00D));
+        assert_eq!(wiring.text(&glyphs, &output), Some("F00d".to_string()));
     }
 
     #[test]
-    fn test_number_determination() {
-        let test = Display {
-            zero: Some(Number {
-                value: 0,
-                chars: "cagedb".to_string(),
-            }),
-            one: Some(Number {
-                value: 1,
-                chars: "ab".to_string(),
-            }),
-            two: None,
-            three: None,
-            four: None,
-            five: None,
-            six: None,
-            seven: None,
-            eight: None,
-            nine: Some(Number {
-                value: 9,
-                chars: "cefabd".to_string(),
-            }),
+    fn custom_glyphs() {
+        // A fourteen-segment display with a glyph per pair of neighbouring segments and one
+        // for the first segment alone, which pins down the whole chain.
+        let mut table = String::from("# chain\n0 a\n");
+        for segment in 0..13u8 {
+            let (from, to) = ((b'a' + segment) as char, (b'b' + segment) as char);
+            table.push_str(&format!("{} {}{}\n", (b'A' + segment) as char, from, to));
+        }
+        let glyphs: Glyphs = table.parse().unwrap();
+        assert_eq!(glyphs.segments(), 14);
+        assert_eq!(glyphs.len(), 14);
+
+        let seen = scramble(&glyphs, 1);
+        let Deduction::Unique(wiring) = deduce(&glyphs, &seen) else {
+            panic!("Wiring should be unique");
         };
+        assert_eq!(
+            wiring.text(&glyphs, &[seen[8], seen[5], seen[12]]),
+            Some("HEL".to_string())
+        );
 
-        assert_eq!(test.determine_number("dcbeag"), 0);
-        assert_eq!(test.determine_number("ba"), 1);
+        assert_eq!(
+            "0 ab\n1 ba".parse::<Glyphs>(),
+            Err(GlyphError::SameSegments {
+                first: '0',
+                second: '1'
+            })
+        );
+        assert_eq!(
+            "0 ab\n1".parse::<Glyphs>(),
+            Err(GlyphError::InvalidLine { line: 2 })
+        );
+    }
+
+    /// The pattern `glyph` makes when segment `s` is driven by wire `wires[s]`.
+    fn encode(glyph: u32, wires: &[usize]) -> u32 {
+        wires
+            .iter()
+            .enumerate()
+            .filter(|(segment, _)| glyph & 1 << segment != 0)
+            .fold(0, |mask, (_, wire)| mask | 1 << wire)
+    }
+
+    #[test]
+    fn differential() {
+        let glyphs = Glyphs::digits();
+        Differential::new("every wiring", |patterns: &Vec<u32>| {
+            brute_force(&glyphs, patterns)
+        })
+        .register("search", |patterns| deduce(&glyphs, patterns))
+        .assert(inputs(60, 8, |rng| {
+            let display = displays(rng, 1);
+            let mut patterns = patterns(&glyphs, display.split('|').next().unwrap());
+            patterns.truncate(rng.gen_range(0..=10));
+            if rng.gen_bool(0.2) {
+                patterns.push(rng.gen_range(0..128));
+            }
+            patterns
+        }));
+    }
+
+    fn wire_names(pattern: u32) -> String {
+        (0..26u8)
+            .filter(|wire| pattern & 1 << wire != 0)
+            .map(|wire| (b'a' + wire) as char)
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn recovers_random_wirings(
+            wires in Just((0..7).collect::<Vec<usize>>()).prop_shuffle(),
+            order in Just((0..10).collect::<Vec<usize>>()).prop_shuffle(),
+            shown in proptest::collection::vec(0..10usize, 4),
+        ) {
+            let glyphs = Glyphs::digits();
+            let lit: Vec<u32> = glyphs.iter().map(|glyph| glyph.segments).collect();
+
+            // Write the line like the puzzle input does, with scrambled letters in each pattern.
+            let mut line: Vec<String> = order.iter().map(|&d| wire_names(encode(lit[d], &wires))).collect();
+            line.push("|".to_string());
+            line.extend(shown.iter().map(|&d| wire_names(encode(lit[d], &wires)).chars().rev().collect::<String>()));
+
+            let expected = shown.iter().fold(0, |number, &d| number * 10 + d as u64);
+            let readings = read_displays(&[line], &glyphs);
+            prop_assert_eq!(&readings[0], &Reading::Number { value: expected, text: format!("{:04}", expected) });
+        }
+
+        #[test]
+        fn any_subset_keeps_the_real_wiring(
+            wires in Just((0..7).collect::<Vec<usize>>()).prop_shuffle(),
+            seen in proptest::collection::vec(0..16usize, 0..8),
+            hex in any::<bool>(),
+        ) {
+            let glyphs = if hex { Glyphs::hexadecimal() } else { Glyphs::digits() };
+            let lit: Vec<u32> = glyphs.iter().map(|glyph| glyph.segments).collect();
+            let seen: Vec<usize> = seen.into_iter().filter(|&g| g < lit.len()).collect();
+            let patterns: Vec<u32> = seen.iter().map(|&g| encode(lit[g], &wires)).collect();
+
+            let wirings = wirings(&glyphs, &patterns);
+            let all: Vec<u32> = lit.iter().map(|&l| encode(l, &wires)).collect();
+            let real = wirings.iter().find(|wiring| {
+                all.iter().enumerate().all(|(g, &p)| wiring.read(&glyphs, p) == Some(g))
+            });
+            prop_assert!(real.is_some(), "The real wiring is missing from {:?}", wirings);
+        }
     }
 }
@@ -0,0 +1,225 @@
+use crate::glyphs::Glyphs;
+use std::ops::ControlFlow;
+
+fn wires(mask: u32, count: usize) -> impl Iterator<Item = usize> {
+    (0..count).filter(move |wire| mask & 1 << wire != 0)
+}
+
+/// Which segment every wire is connected to, as a bitmask with one segment set.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Wiring {
+    segments: Vec<u32>,
+}
+
+impl Wiring {
+    /// The segments that light up when the wires in `pattern` are on.
+    pub(crate) fn translate(&self, pattern: u32) -> u32 {
+        wires(pattern, self.segments.len()).fold(0, |mask, wire| mask | self.segments[wire])
+    }
+
+    /// The value of the glyph `pattern` shows.
+    pub(crate) fn read(&self, glyphs: &Glyphs, pattern: u32) -> Option<usize> {
+        glyphs.value(self.translate(pattern))
+    }
+
+    /// Decodes the patterns as the digits of one number, in the base of the glyph table.
+    /// `None` when a pattern is no glyph or the number does not fit a `u64`.
+    pub(crate) fn number(&self, glyphs: &Glyphs, patterns: &[u32]) -> Option<u64> {
+        patterns.iter().try_fold(0u64, |number, &p| {
+            number
+                .checked_mul(glyphs.len() as u64)?
+                .checked_add(self.read(glyphs, p)? as u64)
+        })
+    }
+
+    pub(crate) fn text(&self, glyphs: &Glyphs, patterns: &[u32]) -> Option<String> {
+        patterns
+            .iter()
+            .map(|&p| glyphs.symbol(self.read(glyphs, p)?))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Deduction {
+    Unique(Wiring),
+    /// More than one wiring explains the patterns.
+    Ambiguous,
+    /// No wiring shows a glyph for every pattern.
+    Impossible,
+}
+
+/// Keeps the first wiring `visit` is called with, and stops it at the second.
+fn tally(visit: impl FnOnce(&mut dyn FnMut(Wiring) -> ControlFlow<()>)) -> Deduction {
+    let mut first: Option<Wiring> = None;
+    let mut ambiguous = false;
+    visit(&mut |wiring| {
+        if first.is_some() {
+            ambiguous = true;
+            return ControlFlow::Break(());
+        }
+        first = Some(wiring);
+        ControlFlow::Continue(())
+    });
+    match first {
+        Some(_) if ambiguous => Deduction::Ambiguous,
+        Some(wiring) => Deduction::Unique(wiring),
+        None => Deduction::Impossible,
+    }
+}
+
+/// Finds the wirings for which each of `patterns` is one of the `glyphs`. The possible
+/// segments of every wire are narrowed down by the glyphs each pattern can still be, and when
+/// that gets stuck the wire with the fewest options is tried with each of them.
+pub(crate) fn deduce(glyphs: &Glyphs, patterns: &[u32]) -> Deduction {
+    tally(|found| each_wiring(glyphs, patterns, found))
+}
+
+fn each_wiring(
+    glyphs: &Glyphs,
+    patterns: &[u32],
+    found: &mut dyn FnMut(Wiring) -> ControlFlow<()>,
+) {
+    let mut patterns = patterns.to_vec();
+    patterns.sort_unstable();
+    patterns.dedup();
+
+    let candidates = vec![glyphs.all(); glyphs.segments()];
+    let _ = search(glyphs, candidates, &patterns, found);
+}
+
+/// Every wiring that explains the patterns, where `deduce` stops at the second.
+#[cfg(test)]
+pub(crate) fn wirings(glyphs: &Glyphs, patterns: &[u32]) -> Vec<Wiring> {
+    let mut wirings: Vec<Wiring> = Vec::new();
+    each_wiring(glyphs, patterns, &mut |wiring| {
+        wirings.push(wiring);
+        ControlFlow::Continue(())
+    });
+    wirings
+}
+
+/// Tries every way to connect the wires, in order. Only feasible for few segments.
+#[cfg(test)]
+pub(crate) fn brute_force(glyphs: &Glyphs, patterns: &[u32]) -> Deduction {
+    fn permute(
+        glyphs: &Glyphs,
+        segments: &mut Vec<u32>,
+        patterns: &[u32],
+        found: &mut dyn FnMut(Wiring) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        if segments.len() == glyphs.segments() {
+            let wiring = Wiring {
+                segments: segments.clone(),
+            };
+            if patterns.iter().all(|&p| wiring.read(glyphs, p).is_some()) {
+                return found(wiring);
+            }
+            return ControlFlow::Continue(());
+        }
+        for segment in 0..glyphs.segments() {
+            if !segments.contains(&(1 << segment)) {
+                segments.push(1 << segment);
+                let flow = permute(glyphs, segments, patterns, found);
+                segments.pop();
+                flow?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    tally(|found| {
+        let _ = permute(glyphs, &mut Vec::new(), patterns, found);
+    })
+}
+
+fn search(
+    glyphs: &Glyphs,
+    mut candidates: Vec<u32>,
+    patterns: &[u32],
+    found: &mut dyn FnMut(Wiring) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    if !propagate(glyphs, &mut candidates, patterns) {
+        return ControlFlow::Continue(());
+    }
+
+    let open = (0..candidates.len())
+        .filter(|&wire| candidates[wire].count_ones() > 1)
+        .min_by_key(|&wire| candidates[wire].count_ones());
+    match open {
+        None => {
+            let wiring = Wiring {
+                segments: candidates,
+            };
+            if patterns.iter().all(|&p| wiring.read(glyphs, p).is_some()) {
+                return found(wiring);
+            }
+            ControlFlow::Continue(())
+        }
+        Some(wire) => {
+            for segment in wires(candidates[wire], glyphs.segments()) {
+                let mut guess = candidates.clone();
+                guess[wire] = 1 << segment;
+                search(glyphs, guess, patterns, found)?;
+            }
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// The glyphs `pattern` can still be given the possible segments of every wire.
+fn options<'a>(
+    glyphs: &'a Glyphs,
+    candidates: &'a [u32],
+    pattern: u32,
+) -> impl Iterator<Item = u32> + 'a {
+    let all = glyphs.all();
+    let count = candidates.len();
+    let on = wires(pattern, count).fold(0, |mask, wire| mask | candidates[wire]);
+    let off = wires(!pattern & all, count).fold(0, |mask, wire| mask | candidates[wire]);
+    glyphs
+        .iter()
+        .map(|glyph| glyph.segments)
+        .filter(move |&lit| {
+            lit.count_ones() == pattern.count_ones()
+                && on & lit == lit
+                && off & !lit & all == !lit & all
+                && wires(pattern, count).all(|wire| candidates[wire] & lit != 0)
+                && wires(!pattern & all, count).all(|wire| candidates[wire] & !lit != 0)
+        })
+}
+
+/// Narrows down the candidates until nothing changes, `false` on a contradiction.
+fn propagate(glyphs: &Glyphs, candidates: &mut [u32], patterns: &[u32]) -> bool {
+    let all = glyphs.all();
+    loop {
+        let before = candidates.to_vec();
+
+        for &pattern in patterns {
+            let (on, off) = options(glyphs, candidates, pattern)
+                .fold((0, 0), |(on, off), lit| (on | lit, off | !lit & all));
+            for (wire, candidate) in candidates.iter_mut().enumerate() {
+                *candidate &= if pattern & 1 << wire != 0 { on } else { off };
+            }
+        }
+
+        // Every segment is connected to exactly one wire.
+        for wire in 0..candidates.len() {
+            if candidates[wire].count_ones() == 1 {
+                let taken = candidates[wire];
+                for (other, candidate) in candidates.iter_mut().enumerate() {
+                    if other != wire {
+                        *candidate &= !taken;
+                    }
+                }
+            }
+        }
+
+        if candidates.contains(&0) {
+            return false;
+        }
+        if candidates == before.as_slice() {
+            return true;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::terrain::Terrain;
+use crate::Location;
+use grid::Grid;
+
+/// Sets of cells, merged by union by size.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(count: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..count).collect(),
+            size: vec![1; count],
+        }
+    }
+
+    fn find(&mut self, mut item: usize) -> usize {
+        while self.parent[item] != item {
+            // Path halving keeps the trees flat
+            self.parent[item] = self.parent[self.parent[item]];
+            item = self.parent[item];
+        }
+        item
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (small, large) = if self.size[a] < self.size[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+    }
+}
+
+/// The smallest rectangle holding every cell of a basin, corners included.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct Bounds {
+    pub(crate) min: (usize, usize),
+    pub(crate) max: (usize, usize),
+}
+
+impl Bounds {
+    fn around(x: usize, y: usize) -> Bounds {
+        Bounds {
+            min: (x, y),
+            max: (x, y),
+        }
+    }
+
+    fn include(&mut self, x: usize, y: usize) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Basin {
+    pub(crate) id: usize,
+    pub(crate) size: usize,
+    /// Empty for a basin with a flat bottom, every cell of it for a low plateau.
+    pub(crate) low_points: Vec<(usize, usize)>,
+    pub(crate) bounds: Bounds,
+}
+
+/// Every cell below the walls labelled with the basin it drains into.
+#[derive(Debug)]
+pub(crate) struct BasinReport {
+    labels: Grid<Option<usize>>,
+    basins: Vec<Basin>,
+}
+
+impl BasinReport {
+    pub(crate) fn new(map: &Grid<Location>, terrain: &Terrain) -> BasinReport {
+        let (rows, cols) = (map.rows(), map.cols());
+        let mut sets = DisjointSet::new(rows * cols);
+        for x in 0..rows {
+            for y in 0..cols {
+                if !terrain.part_of_lake(&map[x][y]) {
+                    continue;
+                }
+                for (nx, ny) in terrain.neighbours(map, x, y) {
+                    if terrain.part_of_lake(&map[nx][ny]) {
+                        sets.union(x * cols + y, nx * cols + ny);
+                    }
+                }
+            }
+        }
+
+        // Basins are numbered in the order their first cell is met, row by row
+        let mut labels: Grid<Option<usize>> = Grid::new(rows, cols);
+        let mut ids: Vec<Option<usize>> = vec![None; rows * cols];
+        let mut basins: Vec<Basin> = Vec::new();
+        for x in 0..rows {
+            for y in 0..cols {
+                if !terrain.part_of_lake(&map[x][y]) {
+                    continue;
+                }
+                let root = sets.find(x * cols + y);
+                let id = *ids[root].get_or_insert_with(|| {
+                    basins.push(Basin {
+                        id: basins.len(),
+                        size: 0,
+                        low_points: Vec::new(),
+                        bounds: Bounds::around(x, y),
+                    });
+                    basins.len() - 1
+                });
+                basins[id].size += 1;
+                basins[id].bounds.include(x, y);
+                labels[x][y] = Some(id);
+            }
+        }
+        for (x, y) in terrain.low_points(map) {
+            if let Some(id) = labels[x][y] {
+                basins[id].low_points.push((x, y));
+            }
+        }
+
+        BasinReport { labels, basins }
+    }
+
+    pub(crate) fn basins(&self) -> &[Basin] {
+        &self.basins
+    }
+
+    /// The basin (`x`, `y`) belongs to, none for a wall.
+    pub(crate) fn basin_at(&self, x: usize, y: usize) -> Option<&Basin> {
+        self.labels
+            .get(x, y)
+            .copied()
+            .flatten()
+            .map(|id| &self.basins[id])
+    }
+
+    /// The `count` largest basins, largest first.
+    pub(crate) fn largest(&self, count: usize) -> Vec<&Basin> {
+        let mut basins: Vec<&Basin> = self.basins.iter().collect();
+        basins.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id)));
+        basins.truncate(count);
+        basins
+    }
+}
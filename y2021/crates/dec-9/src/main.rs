@@ -1,17 +1,24 @@
+mod basins;
+mod terrain;
+mod water;
+
+use crate::basins::BasinReport;
+use crate::terrain::{Adjacency, Terrain};
+use crate::water::Flood;
 use anstyle::Style;
+use digits::parse_digits;
 use grid::Grid;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-#[derive(Debug, Ord, PartialOrd)]
+#[derive(Debug, Default, Clone)]
 struct Location {
     height: u32,
     risk: bool,
-    x: usize,
-    y: usize,
+    /// How deep the cell is under water after rain.
+    water: u32,
 }
 
 impl Location {
@@ -19,8 +26,7 @@ impl Location {
         Location {
             height,
             risk: false,
-            x: 99999,
-            y: 99999,
+            water: 0,
         }
     }
 
@@ -28,27 +34,25 @@ impl Location {
         self.risk = true;
     }
 
-    fn set_coordinate(&mut self, x: usize, y: usize) {
-        self.x = x;
-        self.y = y;
-    }
-
-    fn part_of_lake(&self) -> bool {
-        self.height < 9
+    fn flood(&mut self, level: u32) {
+        self.water = level.saturating_sub(self.height);
     }
-}
-
-impl Eq for Location {}
 
-impl PartialEq for Location {
-    fn eq(&self, other: &Self) -> bool {
-        self.x == other.x && self.y == other.y
+    fn part_of_lake(&self, wall: u32) -> bool {
+        self.height < wall
     }
 }
 
 impl Display for Location {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let number = match self.risk {
+            _ if self.water > 0 => {
+                let blue_style = Style::new()
+                    .fg_color(Some(anstyle::AnsiColor::Blue.into()))
+                    .bold();
+                let blue_style = blue_style.render();
+                format!("{}{}", blue_style, self.height)
+            }
             false => {
                 let green_style = Style::new()
                     .fg_color(Some(anstyle::AnsiColor::Green.into()))
@@ -77,129 +81,97 @@ fn print(grid: &Grid<Location>) {
     }
 }
 
-trait Analyzer {
-    type Value;
-    fn lower(&self, other: Option<&Location>) -> bool;
+/// Every cell as a letter naming its basin, walls as `#`.
+fn print_basins(report: &BasinReport, rows: usize, cols: usize) {
+    for x in 0..rows {
+        let line: String = (0..cols)
+            .map(|y| match report.basin_at(x, y) {
+                Some(basin) => (b'a' + (basin.id % 26) as u8) as char,
+                None => '#',
+            })
+            .collect();
+        println!("{}", line);
+    }
 }
 
-impl Analyzer for Option<&Location> {
-    type Value = Location;
+fn risk_level(map: &Grid<Location>, low_points: &[(usize, usize)]) -> u32 {
+    low_points.iter().map(|&(x, y)| map[x][y].height + 1).sum()
+}
 
-    fn lower(&self, other: Option<&Location>) -> bool {
-        if let Some(other) = other {
-            self.as_ref().unwrap().height < other.height
-        } else {
-            true
-        }
-    }
+/// Part 2: the sizes of the `count` largest basins multiplied.
+fn largest_basins(report: &BasinReport, count: usize) -> usize {
+    report
+        .largest(count)
+        .iter()
+        .map(|basin| basin.size)
+        .product()
 }
 
 fn main() {
+    let mut show_basins = false;
+    let mut rain = false;
+    let mut terrain = Terrain::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--basins" => show_basins = true,
+            "--rain" => rain = true,
+            "--diagonal" => terrain.adjacency = Adjacency::Diagonal,
+            "--plateaus" => terrain.plateaus = true,
+            "--wall" => {
+                terrain.wall = args
+                    .next()
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .expect("--wall needs a height")
+            }
+            _ => panic!("Unknown argument {}", arg),
+        }
+    }
+
     if let Ok(mut map) = read_input("./resources/input-dec-9") {
         // Solution part 1
-        for x in 0..map.rows() {
-            for y in 0..map.cols() {
-                let l = map.get(x, y);
-                let n = map.get(x + 1, y);
-                let mut s = None;
-                if x > 0 {
-                    s = map.get(x - 1, y);
-                }
-                let mut w = None;
-                if y > 0 {
-                    w = map.get(x, y - 1);
-                }
-                let e = map.get(x, y + 1);
-                if l.lower(n) && l.lower(s) && l.lower(w) && l.lower(e) {
-                    let location = map.get_mut(x, y).unwrap();
-                    location.risk();
-                    location.set_coordinate(x, y);
-                }
-            }
+        let lowest_points = terrain.low_points(&map);
+        for &(x, y) in lowest_points.iter() {
+            map[x][y].risk();
         }
-        print(&map);
 
-        let lowest_points: Vec<&Location> = map.iter().filter(|x| x.risk).collect();
-        let sum = lowest_points.iter().map(|x| x.height + 1).sum::<u32>();
-        println!("Sum of risk levels: {}", sum);
+        let report = BasinReport::new(&map, &terrain);
+        let flood = Flood::new(&map, &terrain);
+        if rain {
+            flood.apply(&mut map);
+        }
+        if show_basins {
+            print_basins(&report, map.rows(), map.cols());
+        } else {
+            print(&map);
+        }
+        println!("Sum of risk levels: {}", risk_level(&map, &lowest_points));
 
         // Solution part 2
-        let mut lake_sizes: Vec<i32> = Vec::new();
-        for l in lowest_points.iter() {
-            let mut lake: Vec<Location> = vec![Location {
-                height: l.height,
-                risk: l.risk,
-                x: l.x,
-                y: l.y,
-            }];
-            let mut cont = true;
-
-            while cont {
-                let mut new_discovered_lake: Vec<Location> = Vec::new();
-                for p in lake.iter() {
-                    if let Some(location) = map.get(p.x + 1, p.y) {
-                        let new_location = Location {
-                            height: location.height,
-                            risk: false,
-                            x: p.x + 1,
-                            y: p.y,
-                        };
-                        if location.part_of_lake() && !lake.contains(&new_location) {
-                            new_discovered_lake.push(new_location)
-                        }
-                    }
-                    if p.x > 0 {
-                        if let Some(location) = map.get(p.x - 1, p.y) {
-                            let new_location = Location {
-                                height: location.height,
-                                risk: false,
-                                x: p.x - 1,
-                                y: p.y,
-                            };
-                            if location.part_of_lake() && !lake.contains(&new_location) {
-                                new_discovered_lake.push(new_location)
-                            }
-                        }
-                    }
-                    if p.y > 0 {
-                        if let Some(location) = map.get(p.x, p.y - 1) {
-                            let new_location = Location {
-                                height: location.height,
-                                risk: false,
-                                x: p.x,
-                                y: p.y - 1,
-                            };
-                            if location.part_of_lake() && !lake.contains(&new_location) {
-                                new_discovered_lake.push(new_location)
-                            }
-                        }
-                    }
-                    if let Some(location) = map.get(p.x, p.y + 1) {
-                        let new_location = Location {
-                            height: location.height,
-                            risk: false,
-                            x: p.x,
-                            y: p.y + 1,
-                        };
-                        if location.part_of_lake() && !lake.contains(&new_location) {
-                            new_discovered_lake.push(new_location)
-                        }
-                    }
-                }
-                if new_discovered_lake.is_empty() {
-                    cont = false;
-                }
-                lake.append(&mut new_discovered_lake);
-            }
-            lake.sort();
-            lake.dedup();
-            lake_sizes.push(lake.len() as i32)
-        }
-        lake_sizes.sort_unstable();
+        let flat = report
+            .basins()
+            .iter()
+            .filter(|basin| basin.low_points.is_empty())
+            .count();
         println!(
-            "3 largest lakes multiplied: {}",
-            lake_sizes.pop().unwrap() * lake_sizes.pop().unwrap() * lake_sizes.pop().unwrap()
+            "{} basins, {} of them without a low point",
+            report.basins().len(),
+            flat
         );
+        for basin in report.largest(3) {
+            println!(
+                "Basin {}: {} cells, low points {:?}, rows {}..={}, columns {}..={}",
+                basin.id,
+                basin.size,
+                basin.low_points,
+                basin.bounds.min.0,
+                basin.bounds.max.0,
+                basin.bounds.min.1,
+                basin.bounds.max.1
+            );
+        }
+        println!("3 largest lakes multiplied: {}", largest_basins(&report, 3));
+        println!("Water left after rain: {}", flood.volume());
     }
 }
 
@@ -207,23 +179,254 @@ fn read_input<P>(filename: P) -> io::Result<Grid<Location>>
 where
     P: AsRef<Path>,
 {
-    let mut map: Vec<Vec<Location>> = Vec::new();
-
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
-
-    reader.lines().for_each(|line| {
-        if let Ok(line) = line {
-            map.push(
-                line.trim()
-                    .chars()
-                    .map(|c| Location::new(c.to_digit(10).unwrap() as u32))
-                    .collect(),
-            )
+    let input = fs::read_to_string(filename)?;
+    let rows =
+        parse_digits(&input).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let cols = rows[0].len();
+    let cells = rows.into_iter().flatten().map(Location::new).collect();
+    Ok(Grid::from_vec(cells, cols))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basins::{BasinReport, Bounds};
+    use crate::terrain::{Adjacency, Terrain};
+    use crate::water::Flood;
+    use crate::{largest_basins, read_input, risk_level, Location};
+    use aoc::differential::{inputs, Differential, Rectangle};
+    use aoc::y2021::heightmap;
+    use grid::Grid;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn heights(heights: &Rectangle<u32>) -> Grid<Location> {
+        let cells = heights
+            .0
+            .iter()
+            .flatten()
+            .map(|&h| Location::new(h))
+            .collect();
+        Grid::from_vec(cells, heights.0[0].len())
+    }
+
+    /// Water only stays where every way out passes something at least as high, lowering every
+    /// level until nothing changes.
+    fn relaxed_levels(map: &Grid<Location>, terrain: &Terrain) -> Grid<u32> {
+        let (rows, cols) = (map.rows(), map.cols());
+        let mut levels: Grid<u32> = Grid::init(rows, cols, u32::MAX);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for x in 0..rows {
+                for y in 0..cols {
+                    let edge = x == 0 || y == 0 || x == rows - 1 || y == cols - 1;
+                    let lowest_way_out = terrain
+                        .neighbours(map, x, y)
+                        .map(|(nx, ny)| levels[nx][ny])
+                        .chain(edge.then_some(0))
+                        .min()
+                        .unwrap();
+                    let level = lowest_way_out.max(map[x][y].height);
+                    if level < levels[x][y] {
+                        levels[x][y] = level;
+                        changed = true;
+                    }
+                }
+            }
         }
-    });
-    let cols = map[0].len();
-    let map: Grid<Location> = Grid::from_vec(map.into_iter().flatten().into_iter().collect(), cols);
+        levels
+    }
+
+    fn map(rows: &[&str]) -> Grid<Location> {
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|c| Location::new(c.to_digit(10).unwrap())))
+            .collect();
+        Grid::from_vec(cells, rows[0].len())
+    }
+
+    #[test]
+    fn solution_1() {
+        let map = read_input("../../resources/test-input-dec-9").unwrap();
+        let points = Terrain::default().low_points(&map);
+        assert_eq!(points, vec![(0, 1), (0, 9), (2, 2), (4, 6)]);
+        assert_eq!(risk_level(&map, &points), 15);
+    }
+
+    #[test]
+    fn solution_2() {
+        let map = read_input("../../resources/test-input-dec-9").unwrap();
+        let report = BasinReport::new(&map, &Terrain::default());
+        assert_eq!(report.basins().len(), 4);
+        assert_eq!(largest_basins(&report, 3), 1134);
+
+        let sizes: Vec<usize> = report.largest(4).iter().map(|basin| basin.size).collect();
+        assert_eq!(sizes, vec![14, 9, 9, 3]);
+        let top_right = report.basin_at(0, 9).unwrap();
+        assert_eq!(top_right.size, 9);
+        assert_eq!(top_right.low_points, vec![(0, 9)]);
+        assert_eq!(
+            top_right.bounds,
+            Bounds {
+                min: (0, 5),
+                max: (2, 9)
+            }
+        );
+        assert_eq!(report.basin_at(0, 2), None);
+    }
 
-    Ok(map)
+    #[test]
+    fn flat_bottomed_basins() {
+        let map = map(&["55955", "55959", "99999", "12394"]);
+        let report = BasinReport::new(&map, &Terrain::default());
+        let sizes: Vec<(usize, usize)> = report
+            .basins()
+            .iter()
+            .map(|basin| (basin.size, basin.low_points.len()))
+            .collect();
+        assert_eq!(sizes, vec![(4, 0), (3, 0), (3, 1), (1, 1)]);
+        assert_eq!(largest_basins(&report, 3), 36);
+    }
+
+    #[test]
+    fn wall_height() {
+        let map = read_input("../../resources/test-input-dec-9").unwrap();
+        let terrain = Terrain {
+            wall: 8,
+            ..Terrain::default()
+        };
+        let report = BasinReport::new(&map, &terrain);
+        let sizes: Vec<usize> = report.largest(3).iter().map(|basin| basin.size).collect();
+        assert_eq!(sizes, vec![9, 7, 6]);
+        // Raising the walls past every height floods the whole map into one basin
+        let terrain = Terrain {
+            wall: 10,
+            ..Terrain::default()
+        };
+        assert_eq!(largest_basins(&BasinReport::new(&map, &terrain), 3), 50);
+    }
+
+    #[test]
+    fn diagonal_adjacency() {
+        let map = map(&["1929", "9193", "2999"]);
+        let orthogonal = Terrain::default();
+        assert_eq!(
+            orthogonal.low_points(&map),
+            vec![(0, 0), (0, 2), (1, 1), (1, 3), (2, 0)]
+        );
+        assert_eq!(BasinReport::new(&map, &orthogonal).basins().len(), 5);
+
+        let diagonal = Terrain {
+            adjacency: Adjacency::Diagonal,
+            ..Terrain::default()
+        };
+        // The two ones touch at a corner, so neither is lower than all of its neighbours
+        assert_eq!(diagonal.low_points(&map), vec![]);
+        let plateaus = Terrain {
+            plateaus: true,
+            ..diagonal
+        };
+        assert_eq!(plateaus.low_points(&map), vec![(0, 0), (1, 1)]);
+        let report = BasinReport::new(&map, &diagonal);
+        assert_eq!(report.basins().len(), 1);
+        assert_eq!(report.basins()[0].size, 5);
+    }
+
+    #[test]
+    fn plateaus() {
+        let map = map(&["3345", "3356", "5567", "9994"]);
+        assert_eq!(Terrain::default().low_points(&map), vec![(3, 3)]);
+        let terrain = Terrain {
+            plateaus: true,
+            ..Terrain::default()
+        };
+        let points = terrain.low_points(&map);
+        assert_eq!(points, vec![(0, 0), (0, 1), (1, 0), (1, 1), (3, 3)]);
+        assert_eq!(risk_level(&map, &points), 21);
+        let report = BasinReport::new(&map, &terrain);
+        assert_eq!(report.basin_at(0, 0).unwrap().low_points.len(), 5);
+    }
+
+    #[test]
+    fn rain() {
+        let mut map = map(&["99999", "91219", "99929", "99929"]);
+        let flood = Flood::new(&map, &Terrain::default());
+        assert_eq!(flood.level(1, 1), Some(2));
+        assert_eq!(flood.level(1, 3), Some(2));
+        assert_eq!(flood.level(2, 3), Some(2));
+        assert_eq!(flood.level(3, 3), Some(2));
+        assert_eq!(flood.volume(), 2);
+        flood.apply(&mut map);
+        assert_eq!(map[1][1].water, 1);
+        assert_eq!(map[1][2].water, 0);
+
+        // Diagonally the water escapes through the corner at the bottom right
+        let diagonal = Terrain {
+            adjacency: Adjacency::Diagonal,
+            ..Terrain::default()
+        };
+        let map = self::map(&["9999", "9199", "9929", "9995"]);
+        assert_eq!(Flood::new(&map, &Terrain::default()).volume(), 15);
+        assert_eq!(Flood::new(&map, &diagonal).volume(), 7);
+    }
+
+    #[test]
+    fn rain_matches_relaxation() {
+        let map = read_input("../../resources/test-input-dec-9").unwrap();
+        let mut rng = StdRng::seed_from_u64(2021);
+        let mut maps = vec![map];
+        for _ in 0..20 {
+            let cells = (0..12 * 9)
+                .map(|_| Location::new(rng.gen_range(0..10)))
+                .collect();
+            maps.push(Grid::from_vec(cells, 9));
+        }
+
+        for map in maps.iter() {
+            for adjacency in [Adjacency::Orthogonal, Adjacency::Diagonal] {
+                let terrain = Terrain {
+                    adjacency,
+                    ..Terrain::default()
+                };
+                let levels = relaxed_levels(map, &terrain);
+                let flood = Flood::new(map, &terrain);
+                for x in 0..map.rows() {
+                    for y in 0..map.cols() {
+                        assert_eq!(flood.level(x, y), Some(levels[x][y]));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn differential() {
+        for adjacency in [Adjacency::Orthogonal, Adjacency::Diagonal] {
+            let terrain = Terrain {
+                adjacency,
+                ..Terrain::default()
+            };
+            Differential::new("relaxation", |cells: &Rectangle<u32>| {
+                relaxed_levels(&heights(cells), &terrain)
+                    .iter()
+                    .map(|&level| Some(level))
+                    .collect::<Vec<Option<u32>>>()
+            })
+            .register("priority queue", |cells| {
+                let map = heights(cells);
+                let flood = Flood::new(&map, &terrain);
+                (0..map.rows())
+                    .flat_map(|x| (0..map.cols()).map(move |y| (x, y)))
+                    .map(|(x, y)| flood.level(x, y))
+                    .collect()
+            })
+            .assert(inputs(50, 9, |rng| {
+                let map = heightmap(rng, 8, 11).unwrap();
+                let rows = map
+                    .lines()
+                    .map(|row| row.chars().map(|c| c.to_digit(10).unwrap()).collect());
+                Rectangle(rows.collect())
+            }));
+        }
+    }
 }
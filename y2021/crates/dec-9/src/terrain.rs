@@ -0,0 +1,124 @@
+use crate::Location;
+use grid::Grid;
+
+/// Which cells count as touching.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Adjacency {
+    /// Cells sharing an edge.
+    #[default]
+    Orthogonal,
+    /// Cells sharing an edge or a corner.
+    Diagonal,
+}
+
+impl Adjacency {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Adjacency::Orthogonal => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Adjacency::Diagonal => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// How a heightmap is read: where the walls are, which cells touch and what is a low point.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Terrain {
+    /// Cells at least this high are walls between basins.
+    pub(crate) wall: u32,
+    pub(crate) adjacency: Adjacency,
+    /// A region of equal height lower than everything around it is a low point as a whole.
+    pub(crate) plateaus: bool,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain {
+            wall: 9,
+            adjacency: Adjacency::Orthogonal,
+            plateaus: false,
+        }
+    }
+}
+
+impl Terrain {
+    pub(crate) fn part_of_lake(&self, location: &Location) -> bool {
+        location.part_of_lake(self.wall)
+    }
+
+    /// The cells touching (`x`, `y`).
+    pub(crate) fn neighbours(
+        &self,
+        map: &Grid<Location>,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = (map.rows(), map.cols());
+        self.adjacency
+            .offsets()
+            .iter()
+            .filter_map(move |&(dx, dy)| {
+                Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+            })
+            .filter(move |&(x, y)| x < rows && y < cols)
+    }
+
+    /// The cells of the region of equal height around (`x`, `y`), if it is lower than its surroundings.
+    fn low_region(
+        &self,
+        map: &Grid<Location>,
+        x: usize,
+        y: usize,
+        seen: &mut Grid<bool>,
+    ) -> Option<Vec<(usize, usize)>> {
+        let height = map[x][y].height;
+        let mut region = vec![(x, y)];
+        let mut lowest = true;
+        seen[x][y] = true;
+        let mut next = 0;
+        while next < region.len() {
+            let (cx, cy) = region[next];
+            next += 1;
+            for (nx, ny) in self.neighbours(map, cx, cy) {
+                let other = map[nx][ny].height;
+                if other < height {
+                    lowest = false;
+                } else if other == height && !seen[nx][ny] {
+                    seen[nx][ny] = true;
+                    region.push((nx, ny));
+                }
+            }
+        }
+        let allowed = self.plateaus || region.len() == 1;
+        (lowest && allowed && self.part_of_lake(&map[x][y])).then(|| {
+            region.sort_unstable();
+            region
+        })
+    }
+
+    /// The cells lower than all of their neighbours, row by row. Walls are never low points.
+    pub(crate) fn low_points(&self, map: &Grid<Location>) -> Vec<(usize, usize)> {
+        let mut seen: Grid<bool> = Grid::new(map.rows(), map.cols());
+        let mut points = Vec::new();
+        for x in 0..map.rows() {
+            for y in 0..map.cols() {
+                if seen[x][y] {
+                    continue;
+                }
+                if let Some(region) = self.low_region(map, x, y, &mut seen) {
+                    points.extend(region);
+                }
+            }
+        }
+        points.sort_unstable();
+        points
+    }
+}
@@ -0,0 +1,67 @@
+use crate::terrain::Terrain;
+use crate::Location;
+use grid::Grid;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The heightmap after rain: every basin filled up to where it spills over.
+#[derive(Debug)]
+pub(crate) struct Flood {
+    levels: Grid<u32>,
+    volume: u64,
+}
+
+impl Flood {
+    /// Water runs off the edges of the map, so the levels are settled from the outside in,
+    /// always continuing from the lowest level found so far.
+    pub(crate) fn new(map: &Grid<Location>, terrain: &Terrain) -> Flood {
+        let (rows, cols) = (map.rows(), map.cols());
+        let mut levels: Grid<u32> = Grid::new(rows, cols);
+        let mut settled: Grid<bool> = Grid::new(rows, cols);
+        let mut queue: BinaryHeap<Reverse<(u32, usize, usize)>> = BinaryHeap::new();
+
+        for x in 0..rows {
+            for y in 0..cols {
+                if x == 0 || y == 0 || x == rows - 1 || y == cols - 1 {
+                    settled[x][y] = true;
+                    queue.push(Reverse((map[x][y].height, x, y)));
+                }
+            }
+        }
+
+        let mut volume = 0;
+        while let Some(Reverse((level, x, y))) = queue.pop() {
+            levels[x][y] = level;
+            volume += (level - map[x][y].height) as u64;
+            for (nx, ny) in terrain.neighbours(map, x, y) {
+                if !settled[nx][ny] {
+                    settled[nx][ny] = true;
+                    queue.push(Reverse((level.max(map[nx][ny].height), nx, ny)));
+                }
+            }
+        }
+
+        Flood { levels, volume }
+    }
+
+    /// The height of the water surface at (`x`, `y`), the ground itself where it stays dry.
+    pub(crate) fn level(&self, x: usize, y: usize) -> Option<u32> {
+        self.levels.get(x, y).copied()
+    }
+
+    /// The water retained on the whole map.
+    pub(crate) fn volume(&self) -> u64 {
+        self.volume
+    }
+
+    /// Puts the water on the map, so printing it shows the lakes.
+    pub(crate) fn apply(&self, map: &mut Grid<Location>) {
+        for x in 0..map.rows() {
+            for y in 0..map.cols() {
+                if let Some(level) = self.level(x, y) {
+                    map[x][y].flood(level);
+                }
+            }
+        }
+    }
+}
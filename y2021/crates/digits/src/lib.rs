@@ -0,0 +1,107 @@
+//! Grids of single digits, like the heightmap of day 9 and the octopuses of day 11.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    Empty,
+    InvalidDigit {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "No digits found"),
+            ParseError::InvalidDigit {
+                line,
+                column,
+                found,
+            } => write!(f, "Line {}:{}: {:?} is not a digit", line, column, found),
+            ParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: expected {} digits like the rows above, found {}",
+                line, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The rows of digits, all of the same length. Blank lines are skipped.
+pub fn parse_digits(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
+    let mut rows: Vec<Vec<u32>> = Vec::new();
+    for (line, text) in input.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if text.is_empty() {
+            continue;
+        }
+        let row = text
+            .chars()
+            .enumerate()
+            .map(|(column, c)| {
+                c.to_digit(10).ok_or(ParseError::InvalidDigit {
+                    line,
+                    column: column + 1,
+                    found: c,
+                })
+            })
+            .collect::<Result<Vec<u32>, ParseError>>()?;
+        if let Some(first) = rows.first() {
+            if first.len() != row.len() {
+                return Err(ParseError::RaggedRow {
+                    line,
+                    expected: first.len(),
+                    found: row.len(),
+                });
+            }
+        }
+        rows.push(row);
+    }
+    if rows.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_digits, ParseError};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_digits("21999\r\n\n39878 \n"),
+            Ok(vec![vec![2, 1, 9, 9, 9], vec![3, 9, 8, 7, 8]])
+        );
+        assert_eq!(
+            parse_digits("21999\n3x878"),
+            Err(ParseError::InvalidDigit {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            parse_digits("21999\n3987"),
+            Err(ParseError::RaggedRow {
+                line: 2,
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(parse_digits("\n\n"), Err(ParseError::Empty));
+    }
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use dec_1::parse_input;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Err(error) = parse_input(input) {
+        let _ = error.to_string();
+    }
+});
@@ -0,0 +1,46 @@
+#![no_main]
+
+use dec_10::delimiters::{Check, Delimiters};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let delimiters = Delimiters::brackets();
+    let check = delimiters.check(input);
+    // The linter finds a problem exactly where the checker does
+    assert_eq!(check == Check::Balanced, delimiters.lint(input).is_empty());
+    match check {
+        Check::Balanced => {}
+        Check::Incomplete { completion, .. } => {
+            assert!(!completion.is_empty());
+            let completed = format!("{}{}", input, completion);
+            assert_eq!(delimiters.check(&completed), Check::Balanced);
+        }
+        Check::Corrupted {
+            column,
+            found,
+            expected,
+            ..
+        } => {
+            assert_eq!(input.chars().nth(column), found.chars().next());
+            // Everything before the error is fine, with `expected` as the next closer
+            let before: String = input.chars().take(column).collect();
+            match (delimiters.check(&before), expected) {
+                (Check::Balanced, None) => {}
+                (Check::Incomplete { completion, .. }, Some(expected)) => {
+                    assert!(completion.starts_with(&expected));
+                    assert_ne!(found, expected);
+                }
+                other => panic!("{:?} before column {}", other, column),
+            }
+        }
+        Check::InvalidInput { column, found } => {
+            assert_eq!(input.chars().nth(column), Some(found));
+            assert!(!"()[]{}<>".contains(found));
+            let before: String = input.chars().take(column).collect();
+            assert!(matches!(
+                delimiters.check(&before),
+                Check::Balanced | Check::Incomplete { .. }
+            ));
+        }
+    }
+});
@@ -0,0 +1,29 @@
+#![no_main]
+
+use dec_10::delimiters::{Check, Delimiters};
+use dec_10::parse_table;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    match parse_table(input) {
+        Ok(table) => {
+            // An opener on its own is only missing its closer
+            let delimiters = Delimiters::new(&table.pairs).unwrap();
+            for &(open, close, _, completion) in &table.pairs {
+                match delimiters.check(open) {
+                    Check::Incomplete {
+                        completion: missing,
+                        score,
+                    } => {
+                        assert_eq!(missing, close);
+                        assert_eq!(score, completion.into());
+                    }
+                    other => panic!("{:?} on its own is {:?}", open, other),
+                }
+            }
+        }
+        Err(error) => {
+            let _ = error.to_string();
+        }
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use dec_3::{parse_input, MAX_WIDTH};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    match parse_input(input) {
+        Ok(numbers) => {
+            let width = numbers[0].len();
+            assert!(width <= MAX_WIDTH);
+            for number in numbers {
+                assert_eq!(number.len(), width);
+                assert!(number.iter().all(|&bit| bit == b'0' || bit == b'1'));
+            }
+        }
+        Err(error) => {
+            let _ = error.to_string();
+        }
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use dec_4::parse_input;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    match parse_input(input) {
+        Ok((_, cards)) => {
+            // Every card can be laid out as a grid
+            for rows in cards {
+                assert!(!rows.is_empty() && !rows[0].is_empty());
+                assert!(rows.iter().all(|row| row.len() == rows[0].len()));
+            }
+        }
+        Err(error) => {
+            let _ = error.to_string();
+        }
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use dec_5::parse_input;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    match parse_input(input) {
+        Ok(vents) => {
+            let lines = input.lines().filter(|line| !line.trim().is_empty());
+            assert_eq!(vents.len(), lines.count());
+        }
+        Err(error) => {
+            let _ = error.to_string();
+        }
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use dec_8::parse_input;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    match parse_input(input) {
+        Ok(displays) => {
+            for display in displays {
+                assert_eq!(display.iter().filter(|x| x.as_str() == "|").count(), 1);
+            }
+        }
+        Err(error) => {
+            let _ = error.to_string();
+        }
+    }
+});
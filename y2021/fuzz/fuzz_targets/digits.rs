@@ -0,0 +1,19 @@
+#![no_main]
+
+use digits::parse_digits;
+use libfuzzer_sys::fuzz_target;
+
+// The heightmap of day 9 and the octopuses of day 11 are both read as digit grids.
+fuzz_target!(|input: &str| {
+    match parse_digits(input) {
+        Ok(rows) => {
+            // Rectangular, so it fits in a grid
+            assert!(!rows.is_empty() && !rows[0].is_empty());
+            assert!(rows.iter().all(|row| row.len() == rows[0].len()));
+            assert!(rows.iter().flatten().all(|&digit| digit <= 9));
+        }
+        Err(error) => {
+            let _ = error.to_string();
+        }
+    }
+});